@@ -2,10 +2,11 @@ use arecibo_bellman::{
     calculation::calculate_chain_hash, poseidon_chain_hash_proof::NovaChainHashProof,
     public_params::public_params, PoseidonHashChainCircuit, TEST_SEED,
 };
-use flate2::{write::ZlibEncoder, Compression};
+use bench_alloc::{measure, CountingAllocator, Phase};
 use core::time::Duration;
 use criterion::*;
 use ff::Field;
+use flate2::{write::ZlibEncoder, Compression};
 use rand::SeedableRng;
 use rand_xorshift::XorShiftRng;
 
@@ -17,6 +18,9 @@ targets = bench_recursive_snark_prove, bench_recursive_snark_verify
 
 criterion_main!(recursive_snark);
 
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
 fn bench_recursive_snark_prove(c: &mut Criterion) {
     let cases = vec![3, 10, 100];
 
@@ -28,12 +32,14 @@ fn bench_recursive_snark_prove(c: &mut Criterion) {
     let x3 = Field::random(&mut rng);
 
     // produce public parameters
-    let pp = public_params();
+    let (pp, memory) = measure("Arecibo-Poseidon/Setup", public_params);
+    println!("{memory}");
 
     for num_steps in cases {
         let mut group = c.benchmark_group(format!("Arecibo-Poseidon-num-steps-{}", num_steps));
         group.sample_size(10);
 
+        let phase = Phase::start(format!("Arecibo-Poseidon-num-steps-{}/Prove", num_steps));
         group.bench_function("Prove", |b| {
             b.iter(|| {
                 let initial_state = vec![x0, x1, x2, x3];
@@ -46,6 +52,7 @@ fn bench_recursive_snark_prove(c: &mut Criterion) {
                 NovaChainHashProof::prove_recursively(&pp, &circuits, z0.clone()).unwrap();
             })
         });
+        println!("{}", phase.finish());
         group.finish();
     }
 }
@@ -61,7 +68,8 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
     let x3 = Field::random(&mut rng);
 
     // produce public parameters
-    let pp = public_params();
+    let (pp, memory) = measure("Arecibo-Poseidon/Setup", public_params);
+    println!("{memory}");
 
     println!(
         "Number of constraints per step (primary circuit): {}",
@@ -99,11 +107,12 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
         bincode::serialize_into(&mut encoder, &recursive_snark.0).unwrap();
         let snark_encoded = encoder.finish().unwrap();
         println!(
-          "Arecibo SNARK::len {:?} bytes for case {:?}",
-          snark_encoded.len(),
-          num_steps
+            "Arecibo SNARK::len {:?} bytes for case {:?}",
+            snark_encoded.len(),
+            num_steps
         );
 
+        let phase = Phase::start(format!("Arecibo-Poseidon-num-steps-{}/Verify", num_steps));
         group.bench_function("Verify", |b| {
             b.iter(|| {
                 let res = recursive_snark.verify(&pp, num_steps, z0.clone(), &zi);
@@ -114,6 +123,7 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
                 assert!(res.unwrap());
             })
         });
+        println!("{}", phase.finish());
         group.finish();
     }
 }
@@ -0,0 +1,201 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Global allocator that forwards to `A` and counts the bytes it hands out.
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        Self::with(System)
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    pub const fn with(inner: A) -> Self {
+        CountingAllocator { inner }
+    }
+}
+
+fn add(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+fn sub(size: usize) {
+    CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                add(new_size - layout.size());
+            } else {
+                sub(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Heap bytes currently allocated through `CountingAllocator`.
+pub fn current() -> usize {
+    CURRENT.load(Ordering::Relaxed)
+}
+
+/// Highest value of `current()` since the start or the last `reset_peak`.
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+/// Lowers the peak to the current allocation.
+pub fn reset_peak() {
+    PEAK.store(current(), Ordering::Relaxed);
+}
+
+/// A labeled phase of a benchmark whose heap usage is being tracked.
+pub struct Phase {
+    label: String,
+    start: usize,
+}
+
+impl Phase {
+    pub fn start<L: Into<String>>(label: L) -> Self {
+        reset_peak();
+        Phase {
+            label: label.into(),
+            start: current(),
+        }
+    }
+
+    pub fn finish(self) -> PhaseMemory {
+        PhaseMemory {
+            label: self.label,
+            start: self.start,
+            peak: peak(),
+            end: current(),
+        }
+    }
+}
+
+/// Runs `f` as the phase `label`.
+pub fn measure<T, F: FnOnce() -> T>(label: &str, f: F) -> (T, PhaseMemory) {
+    let phase = Phase::start(label);
+    let res = f();
+    (res, phase.finish())
+}
+
+/// Heap usage of a finished phase, in bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseMemory {
+    pub label: String,
+    pub start: usize,
+    pub peak: usize,
+    pub end: usize,
+}
+
+impl PhaseMemory {
+    /// Peak allocation of the phase on top of what was allocated before it.
+    pub fn peak_increase(&self) -> usize {
+        self.peak.saturating_sub(self.start)
+    }
+}
+
+impl fmt::Display for PhaseMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} memory: peak {} (+{}), start {}, end {}",
+            self.label,
+            Bytes(self.peak),
+            Bytes(self.peak_increase()),
+            Bytes(self.start),
+            Bytes(self.end)
+        )
+    }
+}
+
+struct Bytes(usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            write!(f, "{} B", self.0)
+        } else {
+            write!(f, "{value:.2} {}", UNITS[unit])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOC: CountingAllocator = CountingAllocator::new();
+
+    #[test]
+    fn test_phase_peak() {
+        // The test harness allocates from other threads, so only lower bounds hold.
+        let (len, memory) = measure("alloc", || {
+            let buffer = vec![1u8; 1 << 20];
+            buffer.len()
+        });
+
+        assert_eq!(len, 1 << 20);
+        assert!(memory.peak >= 1 << 20);
+        assert!(memory.peak >= memory.end);
+    }
+
+    #[test]
+    fn test_bytes_display() {
+        assert_eq!(Bytes(512).to_string(), "512 B");
+        assert_eq!(Bytes(1536).to_string(), "1.50 KiB");
+        assert_eq!(Bytes(3 << 30).to_string(), "3.00 GiB");
+    }
+}
@@ -0,0 +1,27 @@
+pragma circom 2.0.3;
+
+include "../sha256/sha256_bytes.circom";
+
+// Nova-Scotia folds circuits with a public `step_in` and a `step_out` of the same size,
+// this wraps the chained SHA256 of sha256_test.circom into that shape.
+template Main(depth_per_fold) {
+    signal input step_in[32];
+    signal output step_out[32];
+
+    signal value[depth_per_fold+1][32];
+
+    component hasher[depth_per_fold];
+
+    value[0] <== step_in;
+
+    for (var i = 0; i < depth_per_fold; i++) {
+        hasher[i] = Sha256Bytes(32);
+        hasher[i].in <== value[i];
+
+        value[i+1] <== hasher[i].out;
+    }
+
+    step_out <== value[depth_per_fold];
+}
+
+component main { public [step_in] } = Main(1);
@@ -0,0 +1,193 @@
+use bench_alloc::{CountingAllocator, Phase};
+use core::time::Duration;
+use criterion::*;
+
+use ff::PrimeField;
+use halo2_aggregation::{Scheme, Shplonk};
+use halo2_common::application::{HashCircuit, MySpec};
+use halo2_common::ipa::{self, PastaHashCircuit};
+use halo2_common::keystore::KeyStore;
+use halo2_common::kzg;
+use halo2_curves::bn256::Fr;
+use halo2_proofs::halo2curves as halo2_curves;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use snark_verifier_sdk::CircuitExt;
+
+criterion_group! {
+    name = ipa_snark;
+    config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+    targets = bench_ipa_proove, bench_ipa_verify, bench_kzg_proove, bench_kzg_verify
+}
+
+criterion_main!(ipa_snark);
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
+type KzgHashCircuit = HashCircuit<Fr, MySpec<9, 8>, 9, 8, 8>;
+
+const K: u32 = 8;
+
+fn random_message<F: PrimeField>() -> [F; 8] {
+    [(); 8].map(|_| F::from(OsRng.next_u32() as u64))
+}
+
+// Without aggregation `num-steps-k` is k separate hash proofs, batch verified with IPA. The
+// KZG benches below prove and batch verify the same proofs over BN254, with the same Blake2b
+// transcript, as the baseline for the cost of a transparent setup; `poseidon` aggregates
+// them instead.
+//
+// The Prove benches include the batch verification of the proofs they produce.
+fn bench_ipa_proove(c: &mut Criterion) {
+    let phase = Phase::start("Halo2-IPA-Poseidon/Setup");
+    let params = ipa::setup(K);
+    let pk = ipa::gen_pk(&params, &PastaHashCircuit::new(random_message()));
+    println!("{}", phase.finish());
+
+    let cases = vec![3, 10, 100];
+
+    for k in cases {
+        let name = format!("Halo2-IPA-Poseidon-num-steps-{}", k);
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let circuits: Vec<_> = (0..k)
+            .map(|_| PastaHashCircuit::new(random_message()))
+            .collect();
+
+        let phase = Phase::start(format!("{}/Prove", name));
+        group.bench_function("Prove", |b| {
+            b.iter(|| {
+                let proofs: Vec<_> = circuits
+                    .iter()
+                    .map(|circuit| {
+                        let instances = circuit.instances();
+                        let proof = ipa::prove(&params, &pk, *circuit, &[&instances[0][..]]);
+                        (proof, instances)
+                    })
+                    .collect();
+
+                assert!(ipa::batch_verify(&params, pk.get_vk(), &proofs));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
+
+fn bench_ipa_verify(c: &mut Criterion) {
+    let params = ipa::setup(K);
+    let pk = ipa::gen_pk(&params, &PastaHashCircuit::new(random_message()));
+
+    let cases = vec![3, 10, 100];
+
+    for k in cases {
+        let name = format!("Halo2-IPA-Poseidon-num-steps-{}", k);
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let proofs: Vec<_> = (0..k)
+            .map(|_| {
+                let circuit = PastaHashCircuit::new(random_message());
+                let instances = circuit.instances();
+                let proof = ipa::prove(&params, &pk, circuit, &[&instances[0][..]]);
+                (proof, instances)
+            })
+            .collect();
+
+        println!(
+            "Halo2 IPA SNARK::len {:?} bytes for case {:?}",
+            proofs.iter().map(|(proof, _)| proof.len()).sum::<usize>(),
+            k
+        );
+
+        let phase = Phase::start(format!("{}/Verify", name));
+        group.bench_function("Verify", |b| {
+            b.iter(|| {
+                assert!(ipa::batch_verify(&params, pk.get_vk(), &proofs));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
+
+fn bench_kzg_proove(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params = store.srs(K).unwrap();
+    let pk = store
+        .pk(&params, &KzgHashCircuit::new(random_message()))
+        .unwrap();
+
+    let cases = vec![3, 10, 100];
+
+    for k in cases {
+        let name = format!("Halo2-KZG-{}-Poseidon-num-steps-{}", Shplonk::NAME, k);
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let circuits: Vec<_> = (0..k)
+            .map(|_| KzgHashCircuit::new(random_message()))
+            .collect();
+
+        let phase = Phase::start(format!("{}/Prove", name));
+        group.bench_function("Prove", |b| {
+            b.iter(|| {
+                let proofs: Vec<_> = circuits
+                    .iter()
+                    .map(|circuit| {
+                        let instances = circuit.instances();
+                        let proof = kzg::prove(&params, &pk, *circuit, &[&instances[0][..]]);
+                        (proof, instances)
+                    })
+                    .collect();
+
+                assert!(kzg::batch_verify(&params, pk.get_vk(), &proofs));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
+
+fn bench_kzg_verify(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params = store.srs(K).unwrap();
+    let pk = store
+        .pk(&params, &KzgHashCircuit::new(random_message()))
+        .unwrap();
+
+    let cases = vec![3, 10, 100];
+
+    for k in cases {
+        let name = format!("Halo2-KZG-{}-Poseidon-num-steps-{}", Shplonk::NAME, k);
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let proofs: Vec<_> = (0..k)
+            .map(|_| {
+                let circuit = KzgHashCircuit::new(random_message());
+                let instances = circuit.instances();
+                let proof = kzg::prove(&params, &pk, circuit, &[&instances[0][..]]);
+                (proof, instances)
+            })
+            .collect();
+
+        println!(
+            "Halo2 KZG {} SNARK::len {:?} bytes for case {:?}",
+            Shplonk::NAME,
+            proofs.iter().map(|(proof, _)| proof.len()).sum::<usize>(),
+            k
+        );
+
+        let phase = Phase::start(format!("{}/Verify", name));
+        group.bench_function("Verify", |b| {
+            b.iter(|| {
+                assert!(kzg::batch_verify(&params, pk.get_vk(), &proofs));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
@@ -0,0 +1,88 @@
+use bench_alloc::{CountingAllocator, Phase};
+use core::time::Duration;
+use criterion::*;
+
+use halo2_common::keystore::KeyStore;
+use halo2_common::recursion::{
+    gen_recursion_pk, gen_recursion_snark, gen_recursion_step, hash_chain, verify_recursion_snark,
+    HashChainCircuit, K, WIDTH,
+};
+use halo2_curves::bn256::Fr;
+use halo2_proofs::halo2curves as halo2_curves;
+
+criterion_group! {
+    name = recursive_snark;
+    config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+    targets = bench_ivc_proove, bench_ivc_verify
+}
+
+criterion_main!(recursive_snark);
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
+// Every round after the first proves the same circuit against a real predecessor, so
+// proving k rounds takes k times the single step timed here.
+fn bench_ivc_proove(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params = store.srs(K).unwrap();
+
+    let phase = Phase::start("Halo2-GWC-IVC-Poseidon/Setup");
+    let pk = gen_recursion_pk(&params, &store).unwrap();
+    println!("{}", phase.finish());
+
+    let initial_state = [Fr::from(0); WIDTH];
+    let (_, previous) = gen_recursion_snark(&params, &pk, initial_state, 1);
+
+    let mut group = c.benchmark_group("Halo2-GWC-IVC-Poseidon");
+    group.sample_size(10);
+
+    let phase = Phase::start("Halo2-GWC-IVC-Poseidon/Prove-Step");
+    group.bench_function("Prove-Step", |b| {
+        b.iter(|| {
+            let (state, _) = gen_recursion_step(&params, &pk, previous.clone(), initial_state, 1);
+            assert_eq!(state, hash_chain(initial_state, 2));
+        })
+    });
+    println!("{}", phase.finish());
+    group.finish();
+}
+
+fn bench_ivc_verify(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params = store.srs(K).unwrap();
+    let pk = gen_recursion_pk(&params, &store).unwrap();
+
+    let cases = vec![3, 10, 100];
+
+    // one chain, verified at each case's round, so the longest is only proven once
+    let initial_state = [Fr::from(0); WIDTH];
+    let mut state = initial_state;
+    let mut snark = HashChainCircuit::initial_snark(&params, Some(pk.get_vk()));
+    let mut round = 0;
+    for k in cases {
+        while round < k {
+            (state, snark) = gen_recursion_step(&params, &pk, snark, initial_state, round);
+            round += 1;
+        }
+        assert_eq!(HashChainCircuit::state(&snark), state);
+
+        let mut group = c.benchmark_group(format!("Halo2-GWC-IVC-Poseidon-num-steps-{}", k));
+        group.sample_size(10);
+
+        println!(
+            "Halo2 GWC IVC SNARK::len {:?} bytes for case {:?}",
+            snark.proof.len(),
+            k
+        );
+
+        let phase = Phase::start(format!("Halo2-GWC-IVC-Poseidon-num-steps-{}/Verify", k));
+        group.bench_function("Verify", |b| {
+            b.iter(|| {
+                assert!(verify_recursion_snark(&params, pk.get_vk(), &snark));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
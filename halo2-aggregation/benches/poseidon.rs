@@ -0,0 +1,115 @@
+use bench_alloc::{CountingAllocator, Phase};
+use core::time::Duration;
+use criterion::*;
+
+use halo2_aggregation::{
+    aggregate, gen_application_snark, prove_aggregation, verify_aggregation, Application, Gwc,
+    Poseidon2Circuit, PoseidonCircuit, Scheme, Shplonk,
+};
+use halo2_common::keystore::KeyStore;
+use snark_verifier_sdk::CircuitExt;
+
+criterion_group! {
+    name = recursive_snark;
+    config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+    targets = bench_recursive_snark_proove::<Gwc, PoseidonCircuit>,
+        bench_recursive_snark_verify::<Gwc, PoseidonCircuit>,
+        bench_recursive_snark_proove::<Shplonk, PoseidonCircuit>,
+        bench_recursive_snark_verify::<Shplonk, PoseidonCircuit>,
+        bench_recursive_snark_proove::<Gwc, Poseidon2Circuit>,
+        bench_recursive_snark_verify::<Gwc, Poseidon2Circuit>,
+        bench_recursive_snark_proove::<Shplonk, Poseidon2Circuit>,
+        bench_recursive_snark_verify::<Shplonk, Poseidon2Circuit>
+}
+
+criterion_main!(recursive_snark);
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
+fn bench_recursive_snark_proove<S: Scheme, A: Application>(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params_app = store.srs(8).unwrap();
+    let params = store.srs(23).unwrap();
+
+    let cases = vec![3, 10, 100];
+
+    for k in cases {
+        let name = format!("Halo2-{}-{}-num-steps-{}", S::NAME, A::NAME, k);
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let phase = Phase::start(format!("{}/Setup", name));
+        let snarks: Vec<_> = vec![0; k]
+            .into_iter()
+            .map(|_| gen_application_snark::<S, A>(&params_app, &store).unwrap())
+            .collect();
+
+        let (agg_circuit, pk) = aggregate::<S>(&params, snarks, &store).unwrap();
+        println!("{}", phase.finish());
+
+        let instances = agg_circuit.instances();
+
+        let phase = Phase::start(format!("{}/Prove", name));
+        group.bench_function("Prove", |b| {
+            b.iter(|| {
+                let proof = prove_aggregation::<S>(&params, &pk, &agg_circuit);
+
+                // validate proof before caching
+                assert!(verify_aggregation::<S>(
+                    &params,
+                    pk.get_vk(),
+                    &proof,
+                    &instances
+                ));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
+
+fn bench_recursive_snark_verify<S: Scheme, A: Application>(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params_app = store.srs(8).unwrap();
+    let params = store.srs(23).unwrap();
+
+    let cases = vec![3, 10, 100];
+
+    for k in cases {
+        let name = format!("Halo2-{}-{}-num-steps-{}", S::NAME, A::NAME, k);
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let snarks: Vec<_> = vec![0; k]
+            .into_iter()
+            .map(|_| gen_application_snark::<S, A>(&params_app, &store).unwrap())
+            .collect();
+
+        let (agg_circuit, pk) = aggregate::<S>(&params, snarks, &store).unwrap();
+        let instances = agg_circuit.instances();
+        let proof = prove_aggregation::<S>(&params, &pk, &agg_circuit);
+
+        println!(
+            "Halo2 {} {} SNARK::len {:?} bytes for case {:?}",
+            S::NAME,
+            A::NAME,
+            proof.len(),
+            k
+        );
+
+        let phase = Phase::start(format!("{}/Verify", name));
+        group.bench_function("Verify", |b| {
+            b.iter(|| {
+                assert!(verify_aggregation::<S>(
+                    &params,
+                    pk.get_vk(),
+                    &proof,
+                    &instances
+                ));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
@@ -0,0 +1,139 @@
+use bench_alloc::{CountingAllocator, Phase};
+use core::time::Duration;
+use criterion::*;
+
+use halo2_aggregation::{Application, Gwc, PoseidonCircuit, Scheme};
+use halo2_common::aggregation::gen_snark;
+use halo2_common::keystore::KeyStore;
+use halo2_common::tree::{gen_aggregation_tree, verify_aggregation_tree, TreeConfig};
+use halo2_curves::bn256::{Bn256, Fr};
+use halo2_proofs::halo2curves as halo2_curves;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use rand::rngs::OsRng;
+use snark_verifier_sdk::Snark;
+
+criterion_group! {
+    name = aggregation_tree;
+    config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+    targets = bench_aggregation_tree_proove, bench_aggregation_tree_verify
+}
+
+criterion_main!(aggregation_tree);
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
+const FAN_OUT: usize = 10;
+
+/// Degree of the SRS, the largest of the layers.
+const K: u32 = 23;
+
+// nodes above the leaves verify 10 application snarks, the layers above 10 aggregation snarks
+// with their accumulators and forwarded instances
+fn config() -> TreeConfig {
+    TreeConfig {
+        fan_out: FAN_OUT,
+        k: vec![22, 23, 23],
+    }
+}
+
+fn gen_leaves(params: &ParamsKZG<Bn256>, store: &KeyStore, k: usize) -> Vec<Snark> {
+    (0..k)
+        .map(|_| gen_snark(params, 8, PoseidonCircuit::random(OsRng), store).unwrap())
+        .collect()
+}
+
+fn expected_instances(leaves: &[Snark]) -> Vec<Vec<Vec<Fr>>> {
+    leaves.iter().map(|leaf| leaf.instances.clone()).collect()
+}
+
+// Unlike `poseidon`, which aggregates all snarks in one circuit, `num-steps-k` here is k
+// snarks aggregated by a tree of fan-out 10
+fn bench_aggregation_tree_proove(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params = store.srs(K).unwrap();
+
+    let cases = vec![100, 1000];
+
+    for k in cases {
+        let name = format!(
+            "Halo2-{}-{}-tree-{}-num-steps-{}",
+            Gwc::NAME,
+            PoseidonCircuit::NAME,
+            FAN_OUT,
+            k
+        );
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let phase = Phase::start(format!("{}/Setup", name));
+        let leaves = gen_leaves(&params, &store, k);
+        let expected = expected_instances(&leaves);
+        println!("{}", phase.finish());
+
+        let phase = Phase::start(format!("{}/Prove", name));
+        group.bench_function("Prove", |b| {
+            b.iter(|| {
+                let tree =
+                    gen_aggregation_tree(&params, &config(), leaves.clone(), &store).unwrap();
+
+                // the root has to verify and expose every leaf's instances, timed with the proof
+                assert!(verify_aggregation_tree(
+                    &params,
+                    &tree.vk,
+                    &tree.root,
+                    &tree.manifest,
+                    &expected
+                ));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
+
+fn bench_aggregation_tree_verify(c: &mut Criterion) {
+    let store = KeyStore::from_env();
+    let params = store.srs(K).unwrap();
+
+    let cases = vec![100, 1000];
+
+    for k in cases {
+        let name = format!(
+            "Halo2-{}-{}-tree-{}-num-steps-{}",
+            Gwc::NAME,
+            PoseidonCircuit::NAME,
+            FAN_OUT,
+            k
+        );
+        let mut group = c.benchmark_group(&name);
+        group.sample_size(10);
+
+        let leaves = gen_leaves(&params, &store, k);
+        let expected = expected_instances(&leaves);
+        let tree = gen_aggregation_tree(&params, &config(), leaves, &store).unwrap();
+
+        println!(
+            "Halo2 {} {} tree SNARK::len {:?} bytes for case {:?}",
+            Gwc::NAME,
+            PoseidonCircuit::NAME,
+            tree.root.proof.len(),
+            k
+        );
+
+        let phase = Phase::start(format!("{}/Verify", name));
+        group.bench_function("Verify", |b| {
+            b.iter(|| {
+                assert!(verify_aggregation_tree(
+                    &params,
+                    &tree.vk,
+                    &tree.root,
+                    &tree.manifest,
+                    &expected
+                ));
+            })
+        });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
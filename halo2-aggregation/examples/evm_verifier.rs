@@ -0,0 +1,45 @@
+use std::{env, fs, path::PathBuf};
+
+use halo2_aggregation::{
+    aggregate, evm::evm_verify, gen_application_snark, Gwc, PoseidonCircuit, Scheme, Shplonk,
+};
+use halo2_common::keystore::KeyStore;
+use halo2_proofs::halo2curves::bn256::Bn256;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+
+/// Aggregates `num_snarks` Poseidon snarks with `S`, then verifies the aggregation proof in
+/// revm and prints the contract size and gas.
+fn report<S: Scheme>(
+    store: &KeyStore,
+    params_app: &ParamsKZG<Bn256>,
+    params: &ParamsKZG<Bn256>,
+    num_snarks: usize,
+    out_dir: Option<&PathBuf>,
+) {
+    let snarks = (0..num_snarks)
+        .map(|_| gen_application_snark::<S, PoseidonCircuit>(params_app, store).unwrap())
+        .collect();
+    let (agg_circuit, pk) = aggregate::<S>(params, snarks, store).unwrap();
+
+    let yul = out_dir.map(|dir| dir.join(format!("{}.yul", S::NAME.to_lowercase())));
+    let report = evm_verify::<S>(params, &pk, agg_circuit, yul.as_deref()).unwrap();
+    println!("{report} ({num_snarks} snarks)");
+}
+
+// cargo run --release --example evm_verifier -- [num_snarks] [yul_output_dir]
+// Needs `solc` on PATH to compile the generated Yul.
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    let num_snarks = args.get(1).map_or(3, |n| n.parse().unwrap());
+    let out_dir = args.get(2).map(PathBuf::from);
+    if let Some(dir) = &out_dir {
+        fs::create_dir_all(dir).unwrap();
+    }
+
+    let store = KeyStore::from_env();
+    let params_app = store.srs(8).unwrap();
+    let params = store.srs(23).unwrap();
+
+    report::<Gwc>(&store, &params_app, &params, num_snarks, out_dir.as_ref());
+    report::<Shplonk>(&store, &params_app, &params, num_snarks, out_dir.as_ref());
+}
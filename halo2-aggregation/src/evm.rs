@@ -0,0 +1,105 @@
+//! On-chain verification cost of aggregation proofs, measured in an embedded EVM (revm).
+use std::{fmt, path::Path};
+
+use halo2_curves::bn256::{Bn256, G1Affine};
+use halo2_proofs::halo2curves as halo2_curves;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use serde::Serialize;
+use snark_verifier::loader::evm::{deploy_and_call, encode_calldata};
+use snark_verifier_sdk::CircuitExt;
+
+use crate::Scheme;
+
+/// Contract size and gas of verifying one proof.
+#[derive(Clone, Debug, Serialize)]
+pub struct EvmReport {
+    pub scheme: &'static str,
+    /// Creation bytecode, constructor included. This is not the runtime code EIP-170 limits
+    /// to 24576 bytes, which is not measured.
+    pub deployment_size: usize,
+    pub calldata_size: usize,
+    /// Gas of the verifying call, including the 21000 of the transaction.
+    pub verify_gas: u64,
+}
+
+impl fmt::Display for EvmReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} EVM verifier: deployment code {} bytes, calldata {} bytes, verification gas {}",
+            self.scheme, self.deployment_size, self.calldata_size, self.verify_gas
+        )
+    }
+}
+
+/// Verifier contract of `circuit` and the calldata of a proof with the EVM transcript.
+fn gen_evm_call<S: Scheme>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: S::AggregationCircuit,
+    yul: Option<&Path>,
+) -> (Vec<u8>, Vec<u8>) {
+    let num_instance = circuit.num_instance();
+    let instances = circuit.instances();
+
+    let deployment_code =
+        S::gen_evm_verifier::<S::AggregationCircuit>(params, pk.get_vk(), num_instance, yul);
+    let proof = S::gen_evm_proof(params, pk, circuit, instances.clone());
+
+    (deployment_code, encode_calldata(&instances, &proof))
+}
+
+/// Generates the verifier contract of `circuit`, proves it with the EVM transcript, then
+/// deploys and calls the verifier locally. The Yul source is written to `yul` when given.
+///
+/// Fails when the contract reverts, i.e. the proof is rejected.
+pub fn evm_verify<S: Scheme>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: S::AggregationCircuit,
+    yul: Option<&Path>,
+) -> Result<EvmReport, String> {
+    let (deployment_code, calldata) = gen_evm_call::<S>(params, pk, circuit, yul);
+
+    let deployment_size = deployment_code.len();
+    let calldata_size = calldata.len();
+    let verify_gas = deploy_and_call(deployment_code, calldata)?;
+
+    Ok(EvmReport {
+        scheme: S::NAME,
+        deployment_size,
+        calldata_size,
+        verify_gas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_common::keystore::KeyStore;
+
+    use super::*;
+    use crate::{aggregate, gen_application_snark, Gwc, PoseidonCircuit};
+
+    #[test]
+    #[ignore = "aggregates two snarks with a k = 23 setup and needs solc"]
+    fn test_evm_verify_rejects_tampered_proof() {
+        let store = KeyStore::from_env();
+        let params_app = store.srs(8).unwrap();
+        let params = store.srs(23).unwrap();
+
+        let snarks = (0..2)
+            .map(|_| gen_application_snark::<Gwc, PoseidonCircuit>(&params_app, &store).unwrap())
+            .collect();
+        let (agg_circuit, pk) = aggregate::<Gwc>(&params, snarks, &store).unwrap();
+
+        let report = evm_verify::<Gwc>(&params, &pk, agg_circuit.clone(), None).unwrap();
+        assert!(report.verify_gas > 21000);
+
+        // the instances come first in the calldata, 32 big-endian bytes each: flip the low
+        // bit of the first accumulator limb, which moves the accumulator off the proof's
+        let (deployment_code, mut calldata) = gen_evm_call::<Gwc>(&params, &pk, agg_circuit, None);
+        calldata[31] ^= 1;
+        assert!(deploy_and_call(deployment_code, calldata).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use halo2_common::application;
+use halo2_common::keystore::{self, KeyStore};
+use halo2_common::poseidon2::{Poseidon2HashCircuit, Poseidon2Spec};
+use halo2_curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves as halo2_curves;
+use halo2_proofs::plonk::{create_proof, verify_proof, Circuit, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::poly::kzg::multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+use halo2_proofs::poly::VerificationStrategy;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use snark_verifier_sdk::halo2::{
+    gen_snark_gwc, gen_snark_shplonk, PoseidonTranscript, POSEIDON_SPEC,
+};
+use snark_verifier_sdk::{halo2::aggregation::AggregationCircuit, Snark};
+use snark_verifier_sdk::{CircuitExt, NativeLoader, GWC, SHPLONK};
+#[cfg(feature = "loader_evm")]
+use std::path::Path;
+
+#[cfg(feature = "loader_evm")]
+pub mod evm;
+
+/// KZG multi-open scheme of the application and aggregation proofs, together with the
+/// accumulation scheme the aggregation circuit verifies them with.
+pub trait Scheme {
+    /// Used in bench names, e.g. `Halo2-GWC-Poseidon-num-steps-3`.
+    const NAME: &'static str;
+
+    type AggregationCircuit: CircuitExt<Fr> + Clone;
+
+    fn gen_snark<C: CircuitExt<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: C,
+    ) -> Snark;
+
+    fn aggregation_circuit(
+        params: &ParamsKZG<Bn256>,
+        snarks: Vec<Snark>,
+    ) -> Self::AggregationCircuit;
+
+    fn prove<C: Circuit<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: C,
+        instances: &[&[Fr]],
+    ) -> Vec<u8>;
+
+    fn verify(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        proof: &[u8],
+        instances: &[&[Fr]],
+    ) -> bool;
+
+    /// Deployment bytecode of a verifier contract for `vk`, compiled from Yul with `solc`,
+    /// optionally writing the Yul source to `path`.
+    #[cfg(feature = "loader_evm")]
+    fn gen_evm_verifier<C: CircuitExt<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        num_instance: Vec<usize>,
+        path: Option<&Path>,
+    ) -> Vec<u8>;
+
+    /// Proof with the keccak transcript the EVM verifier reads.
+    #[cfg(feature = "loader_evm")]
+    fn gen_evm_proof<C: Circuit<Fr>>(
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        circuit: C,
+        instances: Vec<Vec<Fr>>,
+    ) -> Vec<u8>;
+}
+
+/// Gabizon-Williamson-Ciobotaru multi-open, snark-verifier's `GWC`.
+pub struct Gwc;
+
+/// Boneh-Drake-Fisch-Gabizon multi-open, snark-verifier's `SHPLONK`.
+pub struct Shplonk;
+
+macro_rules! impl_scheme {
+    (
+        $scheme:ident,
+        $name:literal,
+        $as:ty,
+        $gen_snark:ident,
+        $prover:ident,
+        $verifier:ident,
+        $gen_evm_verifier:ident,
+        $gen_evm_proof:ident
+    ) => {
+        impl Scheme for $scheme {
+            const NAME: &'static str = $name;
+
+            type AggregationCircuit = AggregationCircuit<$as>;
+
+            fn gen_snark<C: CircuitExt<Fr>>(
+                params: &ParamsKZG<Bn256>,
+                pk: &ProvingKey<G1Affine>,
+                circuit: C,
+            ) -> Snark {
+                $gen_snark(params, pk, circuit, None::<&str>)
+            }
+
+            fn aggregation_circuit(
+                params: &ParamsKZG<Bn256>,
+                snarks: Vec<Snark>,
+            ) -> Self::AggregationCircuit {
+                AggregationCircuit::<$as>::new(params, snarks)
+            }
+
+            fn prove<C: Circuit<Fr>>(
+                params: &ParamsKZG<Bn256>,
+                pk: &ProvingKey<G1Affine>,
+                circuit: C,
+                instances: &[&[Fr]],
+            ) -> Vec<u8> {
+                let mut transcript =
+                    PoseidonTranscript::<NativeLoader, _>::from_spec(vec![], POSEIDON_SPEC.clone());
+
+                create_proof::<_, $prover<_>, _, _, _, _>(
+                    params,
+                    pk,
+                    &[circuit],
+                    &[instances],
+                    OsRng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            }
+
+            fn verify(
+                params: &ParamsKZG<Bn256>,
+                vk: &VerifyingKey<G1Affine>,
+                proof: &[u8],
+                instances: &[&[Fr]],
+            ) -> bool {
+                let mut transcript = PoseidonTranscript::<NativeLoader, &[u8]>::from_spec(
+                    proof,
+                    POSEIDON_SPEC.clone(),
+                );
+                verify_proof::<_, $verifier<_>, _, _, _>(
+                    params.verifier_params(),
+                    vk,
+                    AccumulatorStrategy::new(params.verifier_params()),
+                    &[instances],
+                    &mut transcript,
+                )
+                .map(VerificationStrategy::<_, $verifier<_>>::finalize)
+                .unwrap_or(false)
+            }
+
+            #[cfg(feature = "loader_evm")]
+            fn gen_evm_verifier<C: CircuitExt<Fr>>(
+                params: &ParamsKZG<Bn256>,
+                vk: &VerifyingKey<G1Affine>,
+                num_instance: Vec<usize>,
+                path: Option<&Path>,
+            ) -> Vec<u8> {
+                snark_verifier_sdk::evm::$gen_evm_verifier::<C>(params, vk, num_instance, path)
+            }
+
+            #[cfg(feature = "loader_evm")]
+            fn gen_evm_proof<C: Circuit<Fr>>(
+                params: &ParamsKZG<Bn256>,
+                pk: &ProvingKey<G1Affine>,
+                circuit: C,
+                instances: Vec<Vec<Fr>>,
+            ) -> Vec<u8> {
+                snark_verifier_sdk::evm::$gen_evm_proof(params, pk, circuit, instances)
+            }
+        }
+    };
+}
+
+impl_scheme!(
+    Gwc,
+    "GWC",
+    GWC,
+    gen_snark_gwc,
+    ProverGWC,
+    VerifierGWC,
+    gen_evm_verifier_gwc,
+    gen_evm_proof_gwc
+);
+impl_scheme!(
+    Shplonk,
+    "SHPLONK",
+    SHPLONK,
+    gen_snark_shplonk,
+    ProverSHPLONK,
+    VerifierSHPLONK,
+    gen_evm_verifier_shplonk,
+    gen_evm_proof_shplonk
+);
+
+/// Circuit of the aggregated snarks.
+pub trait Application: CircuitExt<Fr> {
+    /// Used in bench names, e.g. `Halo2-GWC-Poseidon-num-steps-3`.
+    const NAME: &'static str;
+
+    /// Circuit of a random input.
+    fn random<R: RngCore>(rng: R) -> Self;
+}
+
+/// Poseidon digest of an 8 element message with `Pow5Chip`, in one permutation of width 9.
+pub type PoseidonCircuit = application::HashCircuit<Fr, application::MySpec<9, 8>, 9, 8, 8>;
+
+/// Poseidon2 digest of an 8 element message, in four permutations of width 3.
+pub type Poseidon2Circuit = Poseidon2HashCircuit<Fr, Poseidon2Spec<3>, 3, 8>;
+
+fn random_message<R: RngCore>(mut rng: R) -> [Fr; 8] {
+    [(); 8].map(|_| Fr::from(rng.next_u32() as u64))
+}
+
+// the instances are the natively computed digests of the message
+impl Application for PoseidonCircuit {
+    const NAME: &'static str = "Poseidon";
+
+    fn random<R: RngCore>(rng: R) -> Self {
+        Self::new(random_message(rng))
+    }
+}
+
+impl Application for Poseidon2Circuit {
+    const NAME: &'static str = "Poseidon2";
+
+    fn random<R: RngCore>(rng: R) -> Self {
+        Self::new(random_message(rng))
+    }
+}
+
+/// Proof of an `A` circuit of a random input.
+pub fn gen_application_snark<S: Scheme, A: Application>(
+    params: &ParamsKZG<Bn256>,
+    store: &KeyStore,
+) -> Result<Snark, keystore::Error> {
+    let circuit = A::random(OsRng);
+    let pk = store.pk(params, &circuit)?;
+    Ok(S::gen_snark(params, &pk, circuit))
+}
+
+/// Aggregation circuit of `snarks` and its proving key.
+pub fn aggregate<S: Scheme>(
+    params: &ParamsKZG<Bn256>,
+    snarks: Vec<Snark>,
+    store: &KeyStore,
+) -> Result<(S::AggregationCircuit, ProvingKey<G1Affine>), keystore::Error> {
+    let circuit = S::aggregation_circuit(params, snarks);
+    let pk = store.pk(params, &circuit.without_witnesses())?;
+    Ok((circuit, pk))
+}
+
+pub fn prove_aggregation<S: Scheme>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: &S::AggregationCircuit,
+) -> Vec<u8> {
+    let instances = circuit.instances();
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    S::prove(params, pk, circuit.clone(), &instances)
+}
+
+pub fn verify_aggregation<S: Scheme>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+) -> bool {
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    S::verify(params, vk, proof, &instances)
+}
@@ -0,0 +1,393 @@
+//! Aggregation of snarks of different circuits and degrees, e.g. `HashCircuit`, `StandardPlonk`
+//! and `MyCircuit`, into one proof. Unlike snark-verifier-sdk's `AggregationCircuit`, the
+//! instances of the aggregated snarks are exposed after the accumulator limbs, so verifying the
+//! aggregation proof also checks each application's public inputs.
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::commitment::ParamsKZG,
+    },
+};
+use halo2_wrong_ecc::{
+    integer::rns::Rns,
+    maingate::{MainGateInstructions, RangeInstructions, RegionCtx},
+};
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::native::NativeLoader,
+    pcs::{
+        kzg::{KzgAccumulator, KzgDecidingKey},
+        AccumulationSchemeProver,
+    },
+    system::halo2::{compile, Config},
+    util::arithmetic::fe_to_limbs,
+    verifier::{plonk::PlonkProtocol, SnarkVerifier},
+};
+use snark_verifier_sdk::{
+    halo2::{gen_snark_gwc, PoseidonTranscript, POSEIDON_SPEC},
+    CircuitExt, Snark, SnarkWitness, GWC,
+};
+use std::rc::Rc;
+
+use crate::keystore::{self, KeyStore};
+use crate::recursion::{
+    accumulate, Halo2Loader, PlonkSuccinctVerifier, PlonkVerifier, RecursionConfig, Svk, BITS,
+    LIMBS,
+};
+
+/// Where the instances of an aggregated snark are exposed by the aggregation circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnarkInstances {
+    /// Degree of the snark's circuit.
+    pub k: u32,
+    /// Exposed instances per column, without the limbs of an accumulator the snark carries.
+    pub num_instance: Vec<usize>,
+    /// Row of the first exposed instance in the aggregation circuit's instance column.
+    pub offset: usize,
+}
+
+impl SnarkInstances {
+    /// The snark's instances, per column, among the instances of an aggregation snark.
+    pub fn instances(&self, aggregation_instances: &[Fr]) -> Vec<Vec<Fr>> {
+        self.num_instance
+            .iter()
+            .scan(self.offset, |offset, num_instance| {
+                let instances = aggregation_instances[*offset..*offset + num_instance].to_vec();
+                *offset += num_instance;
+                Some(instances)
+            })
+            .collect()
+    }
+}
+
+/// Instances of a snark other than the limbs of the accumulator it carries, per column.
+fn exposed<T: Clone>(protocol: &PlonkProtocol<G1Affine>, instances: &[Vec<T>]) -> Vec<Vec<T>> {
+    instances
+        .iter()
+        .enumerate()
+        .map(|(column, instances)| {
+            instances
+                .iter()
+                .enumerate()
+                .filter(|(row, _)| {
+                    !protocol
+                        .accumulator_indices
+                        .iter()
+                        .flatten()
+                        .any(|index| *index == (column, *row))
+                })
+                .map(|(_, instance)| instance.clone())
+                .collect()
+        })
+        .collect()
+}
+
+fn succinct_verify<'a>(
+    svk: &Svk,
+    loader: &Rc<Halo2Loader<'a>>,
+    snark: &SnarkWitness,
+) -> (
+    Vec<AssignedCell<Fr, Fr>>,
+    Vec<KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>>,
+) {
+    // each snark's verifying key is a constant of the circuit
+    let protocol = snark.protocol.loaded(loader);
+    let instances = snark
+        .instances
+        .iter()
+        .map(|instances| {
+            instances
+                .iter()
+                .map(|instance| loader.assign_scalar(*instance))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::from_spec(
+        loader,
+        snark.proof(),
+        POSEIDON_SPEC.clone(),
+    );
+    let proof =
+        PlonkSuccinctVerifier::read_proof(svk, &protocol, &instances, &mut transcript).unwrap();
+    let accumulators = PlonkSuccinctVerifier::verify(svk, &protocol, &instances, &proof).unwrap();
+
+    (
+        exposed(&snark.protocol, &instances)
+            .into_iter()
+            .flatten()
+            .map(|instance| instance.into_assigned())
+            .collect(),
+        accumulators,
+    )
+}
+
+/// Aggregates snarks of any circuits over the same SRS, possibly downsized to different
+/// degrees (see `gen_snark`). Its instances are the limbs of the accumulator, followed by the
+/// exposed instances of each snark in order, as laid out by `layout`.
+#[derive(Clone)]
+pub struct AggregationCircuit {
+    svk: Svk,
+    snarks: Vec<SnarkWitness>,
+    layout: Vec<SnarkInstances>,
+    instances: Vec<Fr>,
+    as_proof: Value<Vec<u8>>,
+}
+
+impl AggregationCircuit {
+    pub fn new(params: &ParamsKZG<Bn256>, snarks: Vec<Snark>) -> Self {
+        let svk: Svk = params.get_g()[0].into();
+
+        let accumulators = snarks
+            .iter()
+            .flat_map(|snark| {
+                let mut transcript = PoseidonTranscript::<NativeLoader, _>::from_spec(
+                    snark.proof.as_slice(),
+                    POSEIDON_SPEC.clone(),
+                );
+                let proof = PlonkSuccinctVerifier::read_proof(
+                    &svk,
+                    &snark.protocol,
+                    &snark.instances,
+                    &mut transcript,
+                )
+                .unwrap();
+                PlonkSuccinctVerifier::verify(&svk, &snark.protocol, &snark.instances, &proof)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let (accumulator, as_proof) = {
+            let mut transcript =
+                PoseidonTranscript::<NativeLoader, _>::from_spec(vec![], POSEIDON_SPEC.clone());
+            let accumulator =
+                GWC::create_proof(&Default::default(), &accumulators, &mut transcript, OsRng)
+                    .unwrap();
+            (accumulator, transcript.finalize())
+        };
+
+        let mut instances = [
+            accumulator.lhs.x,
+            accumulator.lhs.y,
+            accumulator.rhs.x,
+            accumulator.rhs.y,
+        ]
+        .into_iter()
+        .flat_map(fe_to_limbs::<_, _, LIMBS, BITS>)
+        .collect::<Vec<_>>();
+        let mut layout = Vec::with_capacity(snarks.len());
+        for snark in &snarks {
+            let exposed = exposed(&snark.protocol, &snark.instances);
+            layout.push(SnarkInstances {
+                k: snark.protocol.domain.k as u32,
+                num_instance: exposed.iter().map(Vec::len).collect(),
+                offset: instances.len(),
+            });
+            instances.extend(exposed.into_iter().flatten());
+        }
+
+        Self {
+            svk,
+            snarks: snarks.into_iter().map(SnarkWitness::from).collect(),
+            layout,
+            instances,
+            as_proof: Value::known(as_proof),
+        }
+    }
+
+    /// Where each aggregated snark's instances are, in the order of the snarks.
+    pub fn layout(&self) -> &[SnarkInstances] {
+        &self.layout
+    }
+
+    /// Identifies the circuit without synthesizing it, by the verifying keys of the aggregated
+    /// snarks (through their transcripts' initial state) and their instances.
+    pub(crate) fn shape(&self) -> (Vec<SnarkInstances>, Vec<Option<Fr>>) {
+        (
+            self.layout.clone(),
+            self.snarks
+                .iter()
+                .map(|snark| snark.protocol.transcript_initial_state)
+                .collect(),
+        )
+    }
+
+    fn as_proof(&self) -> Value<&[u8]> {
+        self.as_proof.as_ref().map(Vec::as_slice)
+    }
+}
+
+impl Circuit<Fr> for AggregationCircuit {
+    type Config = RecursionConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snarks: self
+                .snarks
+                .iter()
+                .map(SnarkWitness::without_witnesses)
+                .collect(),
+            layout: self.layout.clone(),
+            instances: self.instances.clone(),
+            as_proof: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        RecursionConfig::configure(
+            meta,
+            vec![BITS / LIMBS],
+            Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = config.main_gate();
+        let range_chip = config.range_chip();
+
+        range_chip.load_table(&mut layouter)?;
+
+        let instances = layouter.assign_region(
+            || "",
+            |region| {
+                let ctx = RegionCtx::new(region, 0);
+
+                let loader = Halo2Loader::new(config.ecc_chip(), ctx);
+                let mut instances = Vec::new();
+                let mut accumulators = Vec::new();
+                for snark in &self.snarks {
+                    let (exposed, snark_accumulators) = succinct_verify(&self.svk, &loader, snark);
+                    instances.extend(exposed);
+                    accumulators.extend(snark_accumulators);
+                }
+
+                let KzgAccumulator { lhs, rhs } =
+                    accumulate(&loader, accumulators, self.as_proof());
+
+                let lhs = lhs.into_assigned();
+                let rhs = rhs.into_assigned();
+
+                Ok([lhs.x(), lhs.y(), rhs.x(), rhs.y()]
+                    .into_iter()
+                    .flat_map(|coordinate| coordinate.limbs())
+                    .map(|limb| limb.as_ref().clone())
+                    .chain(instances)
+                    .collect::<Vec<_>>())
+            },
+        )?;
+
+        for (row, instance) in instances.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| ""), instance, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CircuitExt<Fr> for AggregationCircuit {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![self.instances.len()]
+    }
+
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    fn accumulator_indices() -> Option<Vec<(usize, usize)>> {
+        Some((0..4 * LIMBS).map(|idx| (0, idx)).collect())
+    }
+}
+
+/// `params` downsized to degree `k`, keeping the same toxic waste.
+pub fn downsize(params: &ParamsKZG<Bn256>, k: u32) -> ParamsKZG<Bn256> {
+    let mut params = params.clone();
+    params.downsize(k);
+    params
+}
+
+/// Snark of `circuit` with `params` downsized to degree `k`, so that it can be aggregated with
+/// snarks of other degrees.
+pub fn gen_snark<C: CircuitExt<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    k: u32,
+    circuit: C,
+    store: &KeyStore,
+) -> Result<Snark, keystore::Error> {
+    let params = downsize(params, k);
+    let pk = store.pk(&params, &circuit)?;
+    Ok(gen_snark_gwc(&params, &pk, circuit, None::<&str>))
+}
+
+/// Proving key of `circuit`, which only depends on the circuits and degrees it aggregates.
+pub fn gen_aggregation_pk(
+    params: &ParamsKZG<Bn256>,
+    circuit: &AggregationCircuit,
+    store: &KeyStore,
+) -> Result<ProvingKey<G1Affine>, keystore::Error> {
+    store.pk(params, &circuit.without_witnesses())
+}
+
+pub fn gen_aggregation_snark(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: AggregationCircuit,
+) -> Snark {
+    gen_snark_gwc(params, pk, circuit, None::<&str>)
+}
+
+/// Verifies an aggregation snark against `vk`, including the deferred pairing check of its
+/// accumulator, and that the aggregated snarks, laid out by `layout`, had the `expected`
+/// instances per column.
+pub fn verify_aggregation_snark(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    snark: &Snark,
+    layout: &[SnarkInstances],
+    expected: &[Vec<Vec<Fr>>],
+) -> bool {
+    // the number of instances is taken from the layout, trailing zeros would not change the
+    // evaluation of the instance polynomial
+    let num_instance = 4 * LIMBS
+        + layout
+            .iter()
+            .flat_map(|snark| &snark.num_instance)
+            .sum::<usize>();
+    if layout.len() != expected.len()
+        || snark.instances.len() != 1
+        || snark.instances[0].len() != num_instance
+        || layout
+            .iter()
+            .zip(expected)
+            .any(|(layout, expected)| layout.instances(&snark.instances[0]) != *expected)
+    {
+        return false;
+    }
+
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg()
+            .with_num_instance(vec![num_instance])
+            .with_accumulator_indices(AggregationCircuit::accumulator_indices()),
+    );
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let mut transcript = PoseidonTranscript::<NativeLoader, _>::from_spec(
+        snark.proof.as_slice(),
+        POSEIDON_SPEC.clone(),
+    );
+    match PlonkVerifier::read_proof(&dk, &protocol, &snark.instances, &mut transcript) {
+        Ok(proof) => PlonkVerifier::verify(&dk, &protocol, &snark.instances, &proof).is_ok(),
+        Err(_) => false,
+    }
+}
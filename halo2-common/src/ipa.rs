@@ -0,0 +1,105 @@
+//! Proving over the Pasta curves with IPA commitments. Unlike KZG there is no trusted setup,
+//! the parameters are hashed to the curve and only depend on `k`, at the price of a
+//! verifier that is linear in the circuit size.
+use halo2_proofs::halo2curves::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+use halo2_proofs::poly::ipa::strategy::AccumulatorStrategy;
+use halo2_proofs::poly::VerificationStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+use rand::rngs::OsRng;
+
+use crate::application::{HashCircuit, MySpec};
+
+/// The Poseidon digest of an 8 element message, as aggregated over BN254, over `Fp`, the
+/// scalar field of Vesta.
+pub type PastaHashCircuit = HashCircuit<Fp, MySpec<9, 8>, 9, 8, 8>;
+
+/// Transparent parameters of degree `k`.
+pub fn setup(k: u32) -> ParamsIPA<EqAffine> {
+    ParamsIPA::new(k)
+}
+
+pub fn gen_pk<C: Circuit<Fp>>(params: &ParamsIPA<EqAffine>, circuit: &C) -> ProvingKey<EqAffine> {
+    let vk = keygen_vk(params, circuit).unwrap();
+    keygen_pk(params, vk, circuit).unwrap()
+}
+
+pub fn prove<C: Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &[&[Fp]],
+) -> Vec<u8> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    transcript.finalize()
+}
+
+pub fn verify(
+    params: &ParamsIPA<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[&[Fp]],
+) -> bool {
+    accumulate(
+        params,
+        vk,
+        AccumulatorStrategy::new(params),
+        proof,
+        instances,
+    )
+    .map(VerificationStrategy::<_, VerifierIPA<_>>::finalize)
+    .unwrap_or(false)
+}
+
+/// Verifies `proofs` of the same circuit together. Each proof only folds its IPA opening
+/// into the accumulator, so the multi-scalar multiplication is done once for the batch.
+pub fn batch_verify(
+    params: &ParamsIPA<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proofs: &[(Vec<u8>, Vec<Vec<Fp>>)],
+) -> bool {
+    proofs
+        .iter()
+        .try_fold(
+            AccumulatorStrategy::new(params),
+            |strategy, (proof, instances)| {
+                let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                accumulate(params, vk, strategy, proof, &instances)
+            },
+        )
+        .map(VerificationStrategy::<_, VerifierIPA<_>>::finalize)
+        .unwrap_or(false)
+}
+
+fn accumulate<'params>(
+    params: &'params ParamsIPA<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    strategy: AccumulatorStrategy<'params, EqAffine>,
+    proof: &[u8],
+    instances: &[&[Fp]],
+) -> Result<AccumulatorStrategy<'params, EqAffine>, Error> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+        params,
+        vk,
+        strategy,
+        &[instances],
+        &mut transcript,
+    )
+}
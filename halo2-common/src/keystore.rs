@@ -0,0 +1,265 @@
+//! SRS, proving and verifying keys stored on disk, so setups are paid once across runs.
+use std::{
+    env, fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use ff::{Field, PrimeField};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine, G2Affine};
+use halo2_proofs::halo2curves::group::{prime::PrimeCurveAffine, Curve};
+use halo2_proofs::{
+    plonk::{keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+/// Environment variable overriding the key store directory.
+pub const KEY_STORE_ENV: &str = "HALO2_KEY_STORE";
+
+/// Under `target/`, next to this crate, so it does not depend on the working directory.
+pub const DEFAULT_KEY_STORE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/keys");
+
+/// Seed of the SRS setup, snark-verifier-sdk's `gen_srs` one.
+pub const SRS_SEED: [u8; 32] = [0; 32];
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    ChecksumMismatch {
+        path: PathBuf,
+        expected: String,
+        found: String,
+    },
+    /// A stored SRS was not set up with `SRS_SEED`.
+    SrsMismatch {
+        path: PathBuf,
+    },
+    Keygen(halo2_proofs::plonk::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::ChecksumMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: checksum {} does not match the stored {}, delete the file to regenerate it",
+                path.display(),
+                found,
+                expected
+            ),
+            Error::SrsMismatch { path } => write!(
+                f,
+                "{}: not set up with the store's seed, delete the file to regenerate it",
+                path.display()
+            ),
+            Error::Keygen(e) => write!(f, "key generation failed: {e:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Files are keyed by `k` for the SRS and by circuit digest and `k` for the keys, each with a
+/// `.sha256` checksum checked before it is read.
+#[derive(Clone, Debug)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        KeyStore { dir: dir.into() }
+    }
+
+    /// `HALO2_KEY_STORE`, or `DEFAULT_KEY_STORE` when unset.
+    pub fn from_env() -> Self {
+        Self::new(
+            env::var_os(KEY_STORE_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_KEY_STORE)),
+        )
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// SRS of degree `k`, generated on first use. As with snark-verifier-sdk's `gen_srs`, the
+    /// setup is seeded so that the SRS of every degree shares the same toxic waste, which
+    /// snarks over a smaller SRS need to be aggregated over a larger one. The seed is part of
+    /// the file name, and a stored SRS of another toxic waste is rejected.
+    pub fn srs(&self, k: u32) -> Result<ParamsKZG<Bn256>, Error> {
+        let path = self.srs_path(k);
+        if let Some(params) = self.load(&path, ParamsKZG::<Bn256>::read)? {
+            // the setup's first draw is the toxic waste `s`, committed to by `s·G2`
+            let s = Fr::random(ChaCha20Rng::from_seed(SRS_SEED));
+            if params.s_g2() != (G2Affine::generator() * s).to_affine() {
+                return Err(Error::SrsMismatch { path });
+            }
+            return Ok(params);
+        }
+
+        let params = ParamsKZG::<Bn256>::setup(k, ChaCha20Rng::from_seed(SRS_SEED));
+        self.save(&path, |writer| params.write(writer))?;
+        Ok(params)
+    }
+
+    /// Proving key of `circuit`, generated on first use. The verifying key is always
+    /// generated to identify the circuit, only the proving key is read from the store.
+    pub fn pk<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<ProvingKey<G1Affine>, Error> {
+        let vk = self.vk(params, circuit)?;
+        let path = self.key_path(&vk, params.k(), "pk");
+        if let Some(pk) = self.load(&path, |reader| {
+            ProvingKey::read::<_, C>(reader, SerdeFormat::RawBytesUnchecked)
+        })? {
+            return Ok(pk);
+        }
+
+        let pk = keygen_pk(params, vk, circuit).map_err(Error::Keygen)?;
+        self.save(&path, |writer| {
+            pk.write(writer, SerdeFormat::RawBytesUnchecked)
+        })?;
+        Ok(pk)
+    }
+
+    /// Verifying key of `circuit`, also written to the store for verifiers without the
+    /// circuit, see `read_vk`.
+    pub fn vk<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<VerifyingKey<G1Affine>, Error> {
+        let vk = keygen_vk(params, circuit).map_err(Error::Keygen)?;
+        let path = self.key_path(&vk, params.k(), "vk");
+        if !path.is_file() {
+            self.save(&path, |writer| {
+                vk.write(writer, SerdeFormat::RawBytesUnchecked)
+            })?;
+        }
+        Ok(vk)
+    }
+
+    /// Stored verifying key of circuit `digest` (see `circuit_digest`) at degree `k`.
+    pub fn read_vk<C: Circuit<Fr>>(
+        &self,
+        digest: &str,
+        k: u32,
+    ) -> Result<VerifyingKey<G1Affine>, Error> {
+        let path = self.dir.join(format!("{digest}-k{k}.vk"));
+        self.load(&path, |reader| {
+            VerifyingKey::read::<_, C>(reader, SerdeFormat::RawBytesUnchecked)
+        })?
+        .ok_or_else(|| Error::Io {
+            path,
+            source: io::ErrorKind::NotFound.into(),
+        })
+    }
+
+    /// Path of the SRS of degree `k`, e.g. `srs-k8-0000000000000000.params`.
+    pub fn srs_path(&self, k: u32) -> PathBuf {
+        self.dir
+            .join(format!("srs-k{k}-{}.params", hex::encode(&SRS_SEED[..8])))
+    }
+
+    fn key_path(&self, vk: &VerifyingKey<G1Affine>, k: u32, extension: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}-k{k}.{extension}", circuit_digest(vk)))
+    }
+
+    /// `None` if `path` was never stored.
+    fn load<T>(
+        &self,
+        path: &Path,
+        read: impl FnOnce(&mut BufReader<File>) -> io::Result<T>,
+    ) -> Result<Option<T>, Error> {
+        let checksum_path = with_suffix(path, ".sha256");
+        if !path.is_file() || !checksum_path.is_file() {
+            return Ok(None);
+        }
+        let io_error = |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let expected = fs::read_to_string(&checksum_path).map_err(io_error)?;
+        let found = sha256(path).map_err(io_error)?;
+        if expected.trim() != found {
+            return Err(Error::ChecksumMismatch {
+                path: path.to_path_buf(),
+                expected: expected.trim().to_string(),
+                found,
+            });
+        }
+
+        let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+        read(&mut reader).map(Some).map_err(io_error)
+    }
+
+    /// Writes through a temporary file, so an interrupted run leaves nothing to load.
+    fn save(
+        &self,
+        path: &Path,
+        write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    ) -> Result<(), Error> {
+        let io_error = |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        fs::create_dir_all(&self.dir).map_err(io_error)?;
+        let tmp = with_suffix(path, ".tmp");
+        let mut writer = BufWriter::new(File::create(&tmp).map_err(io_error)?);
+        write(&mut writer).map_err(io_error)?;
+        writer.flush().map_err(io_error)?;
+        drop(writer);
+
+        fs::write(
+            with_suffix(path, ".sha256"),
+            sha256(&tmp).map_err(io_error)?,
+        )
+        .map_err(io_error)?;
+        fs::rename(&tmp, path).map_err(io_error)
+    }
+}
+
+/// Short identifier of a circuit, derived from its verifying key.
+pub fn circuit_digest(vk: &VerifyingKey<G1Affine>) -> String {
+    hex::encode(&vk.transcript_repr().to_repr()[..8])
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn sha256(path: &Path) -> io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
@@ -0,0 +1,86 @@
+//! Proving over BN254 with KZG commitments and SHPLONK, with the Blake2b transcript and the
+//! batch verification of `ipa`, so that the two only differ in the commitment scheme.
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::plonk::{create_proof, verify_proof, Circuit, Error, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
+use halo2_proofs::poly::VerificationStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+use rand::rngs::OsRng;
+
+pub fn prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+) -> Vec<u8> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    transcript.finalize()
+}
+
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+) -> bool {
+    accumulate(
+        params,
+        vk,
+        AccumulatorStrategy::new(params.verifier_params()),
+        proof,
+        instances,
+    )
+    .map(VerificationStrategy::<_, VerifierSHPLONK<_>>::finalize)
+    .unwrap_or(false)
+}
+
+/// Verifies `proofs` of the same circuit together. Each proof only folds its opening into
+/// the accumulator, so the pairing check is done once for the batch.
+pub fn batch_verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proofs: &[(Vec<u8>, Vec<Vec<Fr>>)],
+) -> bool {
+    proofs
+        .iter()
+        .try_fold(
+            AccumulatorStrategy::new(params.verifier_params()),
+            |strategy, (proof, instances)| {
+                let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                accumulate(params, vk, strategy, proof, &instances)
+            },
+        )
+        .map(VerificationStrategy::<_, VerifierSHPLONK<_>>::finalize)
+        .unwrap_or(false)
+}
+
+fn accumulate<'params>(
+    params: &'params ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    strategy: AccumulatorStrategy<'params, Bn256>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+) -> Result<AccumulatorStrategy<'params, Bn256>, Error> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof::<KZGCommitmentScheme<_>, VerifierSHPLONK<_>, _, _, _>(
+        params.verifier_params(),
+        vk,
+        strategy,
+        &[instances],
+        &mut transcript,
+    )
+}
@@ -1,61 +1,100 @@
 // Based upon https://github.com/privacy-scaling-explorations/snark-verifier/blob/main/snark-verifier/examples/evm-verifier-with-accumulator.rs
+#[cfg(feature = "loader_halo2")]
+pub mod aggregation;
+pub mod ipa;
+pub mod keystore;
+pub mod kzg;
+pub mod poseidon2;
+#[cfg(feature = "loader_halo2")]
+pub mod recursion;
+pub mod sponge;
+#[cfg(feature = "loader_halo2")]
+pub mod tree;
+
 pub mod application {
     use halo2_proofs::halo2curves::bn256::Fr;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
-        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
         poly::Rotation,
     };
-    use ff::Field;
+    use ff::{Field, FromUniformBytes, PrimeField};
     use rand::RngCore;
     use snark_verifier_sdk::CircuitExt;
 
     use halo2_gadgets::poseidon::{
-        primitives::{generate_constants, ConstantLength, Mds, Spec},
+        primitives::{self as poseidon, generate_constants, ConstantLength, Mds, Spec},
         Hash, Pow5Chip, Pow5Config,
     };
     use std::convert::TryInto;
     use std::marker::PhantomData;
 
+    /// Proves that `instance` is the Poseidon digest of `message`, over any field with a
+    /// Poseidon `Spec`, e.g. `Fr` of BN254 for KZG or `Fp` of Pasta for IPA (see `ipa`).
     #[derive(Clone, Copy)]
-    pub struct HashCircuit<S, const WIDTH: usize, const RATE: usize, const L: usize>
+    pub struct HashCircuit<F, S, const WIDTH: usize, const RATE: usize, const L: usize>
     where
-        S: Spec<Fr, WIDTH, RATE> + Clone + Copy,
+        F: Field,
+        S: Spec<F, WIDTH, RATE> + Clone + Copy,
     {
-        pub instance: Fr,
-        pub message_arr: [Fr; L],
-        pub message: Value<[Fr; L]>,
+        pub instance: F,
+        pub message_arr: [F; L],
+        pub message: Value<[F; L]>,
         pub _spec: PhantomData<S>,
     }
 
+    impl<F, S, const WIDTH: usize, const RATE: usize, const L: usize> HashCircuit<F, S, WIDTH, RATE, L>
+    where
+        F: Field,
+        S: Spec<F, WIDTH, RATE> + Copy + Clone,
+    {
+        /// Circuit for `message`, with the digest computed natively as its instance.
+        pub fn new(message: [F; L]) -> Self {
+            Self {
+                instance: Self::digest(message),
+                message_arr: message,
+                message: Value::known(message),
+                _spec: PhantomData,
+            }
+        }
+
+        /// Native Poseidon digest of `message`, as constrained by `synthesize`.
+        pub fn digest(message: [F; L]) -> F {
+            poseidon::Hash::<_, S, ConstantLength<L>, WIDTH, RATE>::init().hash(message)
+        }
+    }
+
     #[derive(Debug, Clone)]
-    pub struct MyConfig<const WIDTH: usize, const RATE: usize, const L: usize> {
+    pub struct MyConfig<F: Field, const WIDTH: usize, const RATE: usize, const L: usize> {
         input: [Column<Advice>; L],
-        poseidon_config: Pow5Config<Fr, WIDTH, RATE>,
+        instance: Column<Instance>,
+        poseidon_config: Pow5Config<F, WIDTH, RATE>,
     }
 
-    impl<S, const WIDTH: usize, const RATE: usize, const L: usize> Circuit<Fr>
-        for HashCircuit<S, WIDTH, RATE, L>
+    impl<F, S, const WIDTH: usize, const RATE: usize, const L: usize> Circuit<F>
+        for HashCircuit<F, S, WIDTH, RATE, L>
     where
-        S: Spec<Fr, WIDTH, RATE> + Copy + Clone,
+        F: Field,
+        S: Spec<F, WIDTH, RATE> + Copy + Clone,
     {
-        type Config = MyConfig<WIDTH, RATE, L>;
+        type Config = MyConfig<F, WIDTH, RATE, L>;
         type FloorPlanner = SimpleFloorPlanner;
         #[cfg(feature = "circuit-params")]
         type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self {
-                instance: Fr::ZERO,
-                message_arr: [Fr::ZERO; L],
+                instance: F::ZERO,
+                message_arr: [F::ZERO; L],
                 message: Value::unknown(),
                 _spec: PhantomData,
             }
         }
 
-        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
             let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
-            let _ = meta.instance_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
             let partial_sbox = meta.advice_column();
 
             let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
@@ -65,6 +104,7 @@ pub mod application {
 
             Self::Config {
                 input: state[..RATE].try_into().unwrap(),
+                instance,
                 poseidon_config: Pow5Chip::configure::<S>(
                     meta,
                     state.try_into().unwrap(),
@@ -78,7 +118,7 @@ pub mod application {
         fn synthesize(
             &self,
             config: Self::Config,
-            mut layouter: impl Layouter<Fr>,
+            mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             let chip = Pow5Chip::construct(config.poseidon_config.clone());
 
@@ -104,16 +144,19 @@ pub mod application {
                 chip,
                 layouter.namespace(|| "init"),
             )?;
-            hasher.hash(layouter.namespace(|| "hash"), message)?;
+            let digest = hasher.hash(layouter.namespace(|| "hash"), message)?;
 
-            Ok(())
+            layouter.constrain_instance(digest.cell(), config.instance, 0)
         }
     }
 
     #[derive(Debug, Clone, Copy)]
     pub struct MySpec<const WIDTH: usize, const RATE: usize>;
 
-    impl<const WIDTH: usize, const RATE: usize> Spec<Fr, WIDTH, RATE> for MySpec<WIDTH, RATE> {
+    impl<F, const WIDTH: usize, const RATE: usize> Spec<F, WIDTH, RATE> for MySpec<WIDTH, RATE>
+    where
+        F: FromUniformBytes<64> + Ord,
+    {
         fn full_rounds() -> usize {
             8
         }
@@ -122,7 +165,7 @@ pub mod application {
             56
         }
 
-        fn sbox(val: Fr) -> Fr {
+        fn sbox(val: F) -> F {
             val.pow_vartime([5])
         }
 
@@ -130,7 +173,7 @@ pub mod application {
             0
         }
 
-        fn constants() -> (Vec<[Fr; WIDTH]>, Mds<Fr, WIDTH>, Mds<Fr, WIDTH>) {
+        fn constants() -> (Vec<[F; WIDTH]>, Mds<F, WIDTH>, Mds<F, WIDTH>) {
             generate_constants::<_, Self, WIDTH, RATE>()
         }
     }
@@ -208,16 +251,17 @@ pub mod application {
             vec![vec![self.0]]
         }
     }
-    impl<S, const WIDTH: usize, const RATE: usize, const L: usize> CircuitExt<Fr>
-    for HashCircuit<S, WIDTH, RATE, L> 
+    impl<F, S, const WIDTH: usize, const RATE: usize, const L: usize> CircuitExt<F>
+    for HashCircuit<F, S, WIDTH, RATE, L> 
     where
-    S: Spec<Fr, WIDTH, RATE> + Copy + Clone,
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Copy + Clone,
     {
         fn num_instance(&self) -> Vec<usize> {
             vec![1]
         }
 
-        fn instances(&self) -> Vec<Vec<Fr>> {
+        fn instances(&self) -> Vec<Vec<F>> {
             vec![vec![self.instance]]
         }
     }
@@ -271,5 +315,563 @@ pub mod application {
             )
         }
     }
+
+    #[derive(Clone, Debug)]
+    pub struct FieldConfig {
+        advice: [Column<Advice>; 2],
+        instance: Column<Instance>,
+        s_mul: Selector,
+    }
+
+    /// Proves that `instance` is `constant·(a·b)³`, the power-of-3 `MyCircuit` of the `halo2`
+    /// crate ported from zcash halo2 to the PSE fork, so its snarks can be aggregated.
+    #[derive(Clone, Copy, Default)]
+    pub struct MyCircuit<F: Field> {
+        pub constant: F,
+        pub a: Value<F>,
+        pub b: Value<F>,
+        pub instance: F,
+    }
+
+    impl<F: Field> MyCircuit<F> {
+        pub fn new(constant: F, a: F, b: F) -> Self {
+            Self {
+                constant,
+                a: Value::known(a),
+                b: Value::known(b),
+                instance: constant * (a * b).cube(),
+            }
+        }
+
+        /// Circuit of random `a` and `b`. The constant is part of the verifying key, so it is
+        /// fixed to 7 as in the `halo2` crate.
+        pub fn rand<R: RngCore>(mut rng: R) -> Self
+        where
+            F: PrimeField,
+        {
+            let [a, b] = [(); 2].map(|_| F::from(rng.next_u32() as u64));
+            Self::new(F::from(7), a, b)
+        }
+    }
+
+    impl<F: Field> Circuit<F> for MyCircuit<F> {
+        type Config = FieldConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "halo2_circuit_params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                constant: self.constant,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [meta.advice_column(), meta.advice_column()];
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+
+            meta.enable_equality(instance);
+            meta.enable_constant(constant);
+            advice.map(|column| meta.enable_equality(column));
+
+            let s_mul = meta.selector();
+            meta.create_gate("mul", |meta| {
+                let lhs = meta.query_advice(advice[0], Rotation::cur());
+                let rhs = meta.query_advice(advice[1], Rotation::cur());
+                let out = meta.query_advice(advice[0], Rotation::next());
+                let s_mul = meta.query_selector(s_mul);
+                vec![s_mul * (lhs * rhs - out)]
+            });
+
+            FieldConfig {
+                advice,
+                instance,
+                s_mul,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            // | a0     | a1       | s_mul |
+            // |--------|----------|-------|
+            // | a      | b        | 1     |
+            // | a·b    | a·b      | 1     |
+            // | (a·b)² | a·b      | 1     |
+            // | (a·b)³ | constant | 1     |
+            // | c      |          |       |
+            let c = layouter.assign_region(
+                || "power of 3",
+                |mut region| {
+                    let ab = self.a.zip(self.b).map(|(a, b)| a * b);
+
+                    region.assign_advice(|| "a", config.advice[0], 0, || self.a)?;
+                    region.assign_advice(|| "b", config.advice[1], 0, || self.b)?;
+                    let ab_cell = region.assign_advice(|| "a·b", config.advice[0], 1, || ab)?;
+                    ab_cell.copy_advice(|| "a·b", &mut region, config.advice[1], 1)?;
+                    region.assign_advice(
+                        || "(a·b)²",
+                        config.advice[0],
+                        2,
+                        || ab.map(|ab| ab.square()),
+                    )?;
+                    ab_cell.copy_advice(|| "a·b", &mut region, config.advice[1], 2)?;
+                    region.assign_advice(
+                        || "(a·b)³",
+                        config.advice[0],
+                        3,
+                        || ab.map(|ab| ab.cube()),
+                    )?;
+                    region.assign_advice_from_constant(
+                        || "constant",
+                        config.advice[1],
+                        3,
+                        self.constant,
+                    )?;
+                    for row in 0..4 {
+                        config.s_mul.enable(&mut region, row)?;
+                    }
+
+                    region.assign_advice(
+                        || "c",
+                        config.advice[0],
+                        4,
+                        || ab.map(|ab| self.constant * ab.cube()),
+                    )
+                },
+            )?;
+
+            layouter.constrain_instance(c.cell(), config.instance, 0)
+        }
+    }
+
+    impl<F: Field> CircuitExt<F> for MyCircuit<F> {
+        fn num_instance(&self) -> Vec<usize> {
+            vec![1]
+        }
+
+        fn instances(&self) -> Vec<Vec<F>> {
+            vec![vec![self.instance]]
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::application::{HashCircuit, MySpec};
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use snark_verifier_sdk::CircuitExt;
+
+    type Circuit = HashCircuit<Fr, MySpec<9, 8>, 9, 8, 8>;
+
+    const K: u32 = 8;
+
+    fn message<F: PrimeField>(offset: u64) -> [F; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| F::from(offset + i))
+    }
+
+    #[test]
+    fn test_hash_circuit_accepts_native_digest() {
+        let circuit = Circuit::new(message(0));
+        let prover = MockProver::run(K, &circuit, circuit.instances()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_hash_circuit_rejects_wrong_instance() {
+        let circuit = Circuit::new(message(0));
+        let instances = vec![vec![circuit.instance + Fr::from(1)]];
+        let prover = MockProver::run(K, &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_hash_circuit_rejects_digest_of_other_message() {
+        let mut circuit = Circuit::new(message(0));
+        circuit.instance = Circuit::digest(message(1));
+        let prover = MockProver::run(K, &circuit, circuit.instances()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_power_of_three_circuit() {
+        use super::application::MyCircuit;
+
+        let circuit = MyCircuit::new(Fr::from(7), Fr::from(2), Fr::from(3));
+        assert_eq!(circuit.instance, Fr::from(7 * 216));
+        let prover = MockProver::run(K, &circuit, circuit.instances()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let instances = vec![vec![circuit.instance + Fr::from(1)]];
+        let prover = MockProver::run(K, &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_sponge_permutation_matches_constant_length_hash() {
+        use super::sponge::permute;
+
+        let message = message(0);
+        let mut state = [Fr::ZERO; 9];
+        state[..8].copy_from_slice(&message);
+        state[8] = Fr::from_u128(8 << 64);
+        permute::<Fr, MySpec<9, 8>, 9, 8>(&mut state);
+        assert_eq!(state[0], Circuit::digest(message));
+    }
+
+    type VariableLengthCircuit =
+        super::sponge::VariableLengthHashCircuit<Fr, MySpec<3, 2>, 3, 2, 6>;
+
+    #[test]
+    fn test_variable_length_hash_circuit_accepts_native_digest() {
+        // empty, within one chunk, filling a chunk and up to the maximum
+        for len in [0, 1, 2, 5, 6] {
+            let circuit = VariableLengthCircuit::new(message::<Fr>(0)[..len].to_vec());
+            let prover = MockProver::run(K, &circuit, circuit.instances()).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_variable_length_hash_circuit_separates_trailing_zeros() {
+        use super::sponge::hash;
+
+        let message = message::<Fr>(1)[..3].to_vec();
+        let digest = hash::<Fr, MySpec<3, 2>, 3, 2>(&message);
+
+        let mut extended = message;
+        extended.push(Fr::ZERO);
+        assert_ne!(hash::<Fr, MySpec<3, 2>, 3, 2>(&extended), digest);
+
+        let circuit = VariableLengthCircuit::new(extended);
+        let prover = MockProver::run(K, &circuit, vec![vec![digest]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // https://github.com/HorizenLabs/poseidon2, BN254 with t = 3
+    #[test]
+    fn test_poseidon2_matches_reference_vectors() {
+        use super::poseidon2::{Poseidon2, Poseidon2Spec};
+
+        let poseidon2 = Poseidon2::<Fr, 3>::new::<Poseidon2Spec<3>>();
+        let expected = [
+            "5297208644449048816064511434384511824916970985131888684874823260532015509555",
+            "21816030159894113985964609355246484851575571273661473159848781012394295965040",
+            "13940986381491601233448981668101586453321811870310341844570924906201623195336",
+        ]
+        .map(|value| Fr::from_str_vartime(value).unwrap());
+        assert_eq!(poseidon2.permute([0u64, 1, 2].map(Fr::from)), expected);
+    }
+
+    #[test]
+    fn test_poseidon2_hash_circuit_accepts_native_digest() {
+        use super::poseidon2::{Poseidon2HashCircuit, Poseidon2Spec};
+
+        // 8 elements fill 4 chunks of rate 2, 5 leave one padded
+        let circuit = Poseidon2HashCircuit::<Fr, Poseidon2Spec<3>, 3, 8>::new(message(0));
+        let prover = MockProver::run(K, &circuit, circuit.instances()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let message: [Fr; 5] = message(0)[..5].try_into().unwrap();
+        let circuit = Poseidon2HashCircuit::<Fr, Poseidon2Spec<3>, 3, 5>::new(message);
+        let prover = MockProver::run(K, &circuit, circuit.instances()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_poseidon2_hash_circuit_rejects_wrong_instance() {
+        use super::poseidon2::{Poseidon2HashCircuit, Poseidon2Spec};
+
+        let circuit = Poseidon2HashCircuit::<Fr, Poseidon2Spec<3>, 3, 8>::new(message(0));
+        let instances = vec![vec![circuit.instance + Fr::from(1)]];
+        let prover = MockProver::run(K, &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_ipa_batch_verifies_hash_proofs() {
+        use super::ipa::{batch_verify, gen_pk, prove, setup, verify, PastaHashCircuit};
+        use halo2_proofs::halo2curves::pasta::Fp;
+
+        let params = setup(K);
+        let circuits = [0, 1].map(|offset| PastaHashCircuit::new(message(offset)));
+        let pk = gen_pk(&params, &circuits[0]);
+
+        let proofs = circuits
+            .iter()
+            .map(|circuit| {
+                let instances = circuit.instances();
+                let proof = prove(&params, &pk, *circuit, &[&instances[0][..]]);
+                (proof, instances)
+            })
+            .collect::<Vec<_>>();
+        assert!(batch_verify(&params, pk.get_vk(), &proofs));
+
+        let wrong = proofs[0].1[0][0] + Fp::from(1);
+        assert!(!verify(&params, pk.get_vk(), &proofs[0].0, &[&[wrong][..]]));
+
+        // one bad proof fails the whole batch
+        let mut swapped = proofs.clone();
+        swapped[0].1 = proofs[1].1.clone();
+        assert!(!batch_verify(&params, pk.get_vk(), &swapped));
+    }
+
+    #[test]
+    fn test_kzg_batch_verifies_hash_proofs() {
+        use super::kzg::{batch_verify, prove, verify};
+        use halo2_proofs::halo2curves::bn256::Bn256;
+        use halo2_proofs::plonk::{keygen_pk, keygen_vk};
+        use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+        use rand::rngs::OsRng;
+
+        let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+        let circuits = [0, 1].map(|offset| Circuit::new(message(offset)));
+        let vk = keygen_vk(&params, &circuits[0]).unwrap();
+        let pk = keygen_pk(&params, vk, &circuits[0]).unwrap();
+
+        let proofs = circuits
+            .iter()
+            .map(|circuit| {
+                let instances = circuit.instances();
+                let proof = prove(&params, &pk, *circuit, &[&instances[0][..]]);
+                (proof, instances)
+            })
+            .collect::<Vec<_>>();
+        assert!(batch_verify(&params, pk.get_vk(), &proofs));
+
+        let wrong = proofs[0].1[0][0] + Fr::from(1);
+        assert!(!verify(&params, pk.get_vk(), &proofs[0].0, &[&[wrong][..]]));
+
+        // one bad proof fails the whole batch
+        let mut swapped = proofs.clone();
+        swapped[0].1 = proofs[1].1.clone();
+        assert!(!batch_verify(&params, pk.get_vk(), &swapped));
+    }
+
+    #[test]
+    fn test_key_store_reuses_checked_files() {
+        use super::keystore::{Error, KeyStore};
+        use halo2_proofs::halo2curves::bn256::Bn256;
+        use halo2_proofs::poly::commitment::{Params, ParamsProver};
+        use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+        use rand::rngs::OsRng;
+        use sha2::{Digest, Sha256};
+        use std::{env, fs, process};
+
+        let dir = env::temp_dir().join(format!("halo2-key-store-{}", process::id()));
+        let store = KeyStore::new(&dir);
+
+        let params = store.srs(K).unwrap();
+        assert_eq!(store.srs(K).unwrap().get_g(), params.get_g());
+
+        let circuit = Circuit::new(message(0));
+        let pk = store.pk(&params, &circuit).unwrap();
+        let stored = store.pk(&params, &circuit).unwrap();
+        assert_eq!(
+            stored.get_vk().transcript_repr(),
+            pk.get_vk().transcript_repr()
+        );
+
+        // flip a bit of the stored SRS
+        let path = store.srs_path(K);
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, bytes).unwrap();
+        assert!(matches!(store.srs(K), Err(Error::ChecksumMismatch { .. })));
+
+        // an SRS of other toxic waste, with a valid checksum
+        let mut bytes = Vec::new();
+        ParamsKZG::<Bn256>::setup(K, OsRng)
+            .write(&mut bytes)
+            .unwrap();
+        fs::write(&path, &bytes).unwrap();
+        let mut checksum_path = path.into_os_string();
+        checksum_path.push(".sha256");
+        fs::write(checksum_path, hex::encode(Sha256::digest(&bytes))).unwrap();
+        assert!(matches!(store.srs(K), Err(Error::SrsMismatch { .. })));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "loader_halo2")]
+    #[test]
+    #[ignore = "aggregates three snarks with a k = 22 setup"]
+    fn test_aggregation_checks_each_application() {
+        use super::aggregation::{
+            gen_aggregation_pk, gen_aggregation_snark, gen_snark, verify_aggregation_snark,
+            AggregationCircuit,
+        };
+        use super::application::{MyCircuit, StandardPlonk};
+        use super::keystore::KeyStore;
+        use rand::rngs::OsRng;
+
+        let store = KeyStore::from_env();
+        let params = store.srs(22).unwrap();
+
+        // three circuits of three degrees over the same SRS
+        let snarks = vec![
+            gen_snark(&params, 8, Circuit::new(message(0)), &store).unwrap(),
+            gen_snark(&params, 7, StandardPlonk::rand(OsRng), &store).unwrap(),
+            gen_snark(&params, 6, MyCircuit::<Fr>::rand(OsRng), &store).unwrap(),
+        ];
+        let expected = snarks
+            .iter()
+            .map(|snark| snark.instances.clone())
+            .collect::<Vec<_>>();
+
+        let circuit = AggregationCircuit::new(&params, snarks);
+        let layout = circuit.layout().to_vec();
+        assert_eq!(
+            layout.iter().map(|snark| snark.k).collect::<Vec<_>>(),
+            [8, 7, 6]
+        );
+
+        let pk = gen_aggregation_pk(&params, &circuit, &store).unwrap();
+        let snark = gen_aggregation_snark(&params, &pk, circuit);
+        assert!(verify_aggregation_snark(
+            &params,
+            pk.get_vk(),
+            &snark,
+            &layout,
+            &expected
+        ));
+
+        // the aggregation proof binds each application's instances
+        let mut wrong = expected.clone();
+        wrong[2][0][0] += Fr::from(1);
+        assert!(!verify_aggregation_snark(
+            &params,
+            pk.get_vk(),
+            &snark,
+            &layout,
+            &wrong
+        ));
+
+        // and rewriting them in the aggregation snark breaks its proof
+        let mut forged = snark.clone();
+        forged.instances[0][layout[2].offset] += Fr::from(1);
+        assert!(!verify_aggregation_snark(
+            &params,
+            pk.get_vk(),
+            &forged,
+            &layout,
+            &wrong
+        ));
+    }
+
+    #[cfg(feature = "loader_halo2")]
+    #[test]
+    fn test_tree_layers() {
+        use super::tree::{Manifest, TreeConfig};
+
+        let config = TreeConfig {
+            fan_out: 10,
+            k: vec![],
+        };
+        // a root over a single batch, then one more layer per power of the fan-out
+        assert_eq!(config.num_layers(1), 1);
+        assert_eq!(config.num_layers(10), 1);
+        assert_eq!(config.num_layers(11), 2);
+        assert_eq!(config.num_layers(100), 2);
+        assert_eq!(config.num_layers(1000), 3);
+        assert_eq!(config.num_layers(1001), 4);
+
+        let manifest = Manifest {
+            fan_out: 10,
+            k: vec![22, 23, 23],
+            leaves: vec![],
+        };
+        assert_eq!(manifest.node(123, 0), 12);
+        assert_eq!(manifest.node(123, 1), 1);
+        assert_eq!(manifest.node(123, 2), 0);
+    }
+
+    #[cfg(feature = "loader_halo2")]
+    #[test]
+    #[ignore = "proves a two layer tree with a k = 22 setup"]
+    fn test_tree_exposes_every_leaf() {
+        use super::aggregation::gen_snark;
+        use super::keystore::KeyStore;
+        use super::tree::{gen_aggregation_tree, verify_aggregation_tree, Error, TreeConfig};
+
+        let store = KeyStore::from_env();
+        let params = store.srs(22).unwrap();
+
+        let leaves = (0..3)
+            .map(|offset| gen_snark(&params, 8, Circuit::new(message(offset)), &store).unwrap())
+            .collect::<Vec<_>>();
+        let expected = leaves
+            .iter()
+            .map(|leaf| leaf.instances.clone())
+            .collect::<Vec<_>>();
+
+        // a root above the SRS can't be downsized to
+        let config = TreeConfig {
+            fan_out: 2,
+            k: vec![22, 23],
+        };
+        assert!(matches!(
+            gen_aggregation_tree(&params, &config, leaves.clone(), &store),
+            Err(Error::Degree { k: 23, srs_k: 22 })
+        ));
+
+        // two nodes of two and one leaf, then the root
+        let config = TreeConfig {
+            fan_out: 2,
+            k: vec![22, 22],
+        };
+        let tree = gen_aggregation_tree(&params, &config, leaves, &store).unwrap();
+        assert_eq!(tree.manifest.leaves.len(), 3);
+        assert_eq!(tree.manifest.node(2, 0), 1);
+        assert!(verify_aggregation_tree(
+            &params,
+            &tree.vk,
+            &tree.root,
+            &tree.manifest,
+            &expected
+        ));
+
+        let mut wrong = expected;
+        wrong.swap(0, 1);
+        assert!(!verify_aggregation_tree(
+            &params,
+            &tree.vk,
+            &tree.root,
+            &tree.manifest,
+            &wrong
+        ));
+    }
+
+    #[cfg(feature = "loader_halo2")]
+    #[test]
+    #[ignore = "proves two rounds with a k = 22 setup"]
+    fn test_recursion_chains_hashes() {
+        use super::keystore::KeyStore;
+        use super::recursion::{
+            gen_recursion_pk, gen_recursion_snark, hash_chain, verify_recursion_snark,
+            HashChainCircuit, K,
+        };
+
+        let store = KeyStore::from_env();
+        let params = store.srs(K).unwrap();
+        let pk = gen_recursion_pk(&params, &store).unwrap();
+
+        let initial_state = [42u64, 43, 44, 45].map(Fr::from);
+        let (state, snark) = gen_recursion_snark(&params, &pk, initial_state, 2);
+
+        assert_eq!(state, hash_chain(initial_state, 2));
+        assert_eq!(HashChainCircuit::initial_state(&snark), initial_state);
+        assert_eq!(HashChainCircuit::state(&snark), state);
+        assert_eq!(HashChainCircuit::round(&snark), Fr::from(1));
+        assert!(verify_recursion_snark(&params, pk.get_vk(), &snark));
+
+        let mut forged = snark.clone();
+        forged.instances[0][HashChainCircuit::STATE_ROW] = hash_chain(initial_state, 3)[0];
+        assert!(!verify_recursion_snark(&params, pk.get_vk(), &forged));
+    }
+}
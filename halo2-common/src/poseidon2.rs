@@ -0,0 +1,691 @@
+//! Poseidon2 (https://eprint.iacr.org/2023/323) permutation chip, with a native reference
+//! implementation and a `HashCircuit`-like wrapper.
+//!
+//! Partial rounds multiply by the cheap internal matrix `M_I = J + diag(d)`, `J` being all
+//! ones, so each output is the state sum plus one scaled element. Like `Pow5Chip`, a row holds
+//! either one full round or two partial rounds.
+use std::array;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul};
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
+};
+use snark_verifier_sdk::CircuitExt;
+
+/// Parameters of a Poseidon2 instance of width `T`, with the `x^5` S-box.
+pub trait Spec<F: PrimeField, const T: usize>: fmt::Debug + Clone + Copy {
+    fn full_rounds() -> usize;
+
+    /// Must be even, partial rounds are laid out in pairs.
+    fn partial_rounds() -> usize;
+
+    /// `d` of the internal matrix `M_I = J + diag(d)`.
+    fn internal_diagonal() -> [F; T];
+
+    /// The Grain LFSR constants of the reference implementation: `T` per full round, one per
+    /// partial round.
+    fn round_constants() -> Vec<[F; T]> {
+        let full_rounds = Self::full_rounds();
+        let partial_rounds = Self::partial_rounds();
+        let mut grain = Grain::new(F::NUM_BITS, T, full_rounds, partial_rounds);
+        let partial = full_rounds / 2..full_rounds / 2 + partial_rounds;
+
+        (0..full_rounds + partial_rounds)
+            .map(|round| {
+                if partial.contains(&round) {
+                    let mut constants = [F::ZERO; T];
+                    constants[0] = grain.next_field_element();
+                    constants
+                } else {
+                    array::from_fn(|_| grain.next_field_element())
+                }
+            })
+            .collect()
+    }
+}
+
+/// The reference instances of widths 2 and 3 for ~254 bit fields, 8 full and 56 partial
+/// rounds. Wider instances need their own `internal_diagonal`.
+#[derive(Debug, Clone, Copy)]
+pub struct Poseidon2Spec<const T: usize>;
+
+impl<F: PrimeField, const T: usize> Spec<F, T> for Poseidon2Spec<T> {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        56
+    }
+
+    fn internal_diagonal() -> [F; T] {
+        let diagonal: &[u64] = match T {
+            2 => &[1, 2],
+            3 => &[1, 1, 2],
+            _ => panic!("no reference internal matrix of width {}", T),
+        };
+        array::from_fn(|i| F::from(diagonal[i]))
+    }
+}
+
+/// Grain LFSR in self-shrinking mode, as used to generate Poseidon and Poseidon2 constants.
+struct Grain {
+    state: [bool; 80],
+}
+
+impl Grain {
+    fn new(num_bits: u32, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut bits = Vec::with_capacity(80);
+        let mut append = |value: u64, len: usize| {
+            bits.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        // prime field, x^alpha S-box
+        append(1, 2);
+        append(0, 4);
+        append(num_bits as u64, 12);
+        append(width as u64, 12);
+        append(full_rounds as u64, 10);
+        append(partial_rounds as u64, 10);
+        append((1 << 30) - 1, 30);
+
+        let mut grain = Grain {
+            state: bits.try_into().unwrap(),
+        };
+        for _ in 0..160 {
+            grain.update();
+        }
+        grain
+    }
+
+    fn update(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.rotate_left(1);
+        self.state[79] = bit;
+        bit
+    }
+
+    /// A pair of bits outputs the second one if the first one is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            if self.update() {
+                return self.update();
+            }
+            self.update();
+        }
+    }
+
+    /// Big-endian `F::NUM_BITS` bits, rejected until they are below the modulus.
+    fn next_field_element<F: PrimeField>(&mut self) -> F {
+        loop {
+            let mut repr = F::Repr::default();
+            for i in (0..F::NUM_BITS as usize).rev() {
+                if self.next_bit() {
+                    repr.as_mut()[i / 8] |= 1 << (i % 8);
+                }
+            }
+            if let Some(element) = Option::from(F::from_repr(repr)) {
+                return element;
+            }
+        }
+    }
+}
+
+/// Field elements natively, expressions in gates.
+trait Term<F>: Clone + Add<Output = Self> + Mul<Output = Self> + Mul<F, Output = Self> {}
+
+impl<F, E> Term<F> for E where E: Clone + Add<Output = E> + Mul<Output = E> + Mul<F, Output = E> {}
+
+fn sbox<F, E: Term<F>>(x: E) -> E {
+    let x2 = x.clone() * x.clone();
+    x2.clone() * x2 * x
+}
+
+fn sum<F, E: Term<F>>(x: &[E]) -> E {
+    x[1..].iter().fold(x[0].clone(), |acc, e| acc + e.clone())
+}
+
+/// `[[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]` in 8 additions.
+fn m4<F: PrimeField, E: Term<F>>(x: &[E]) -> [E; 4] {
+    let (two, four) = (F::from(2), F::from(4));
+    let t0 = x[0].clone() + x[1].clone();
+    let t1 = x[2].clone() + x[3].clone();
+    let t2 = x[1].clone() * two + t1.clone();
+    let t3 = x[3].clone() * two + t0.clone();
+    let t4 = t1 * four + t3.clone();
+    let t5 = t0 * four + t2.clone();
+    [t3 + t5.clone(), t5, t2 + t4.clone(), t4]
+}
+
+/// `circ(2, 1)` and `circ(2, 1, 1)` for widths 2 and 3, `M4` for 4, and `circ(2 M4, M4, ...)`
+/// for larger multiples of 4.
+fn external_matrix<F: PrimeField, E: Term<F>, const T: usize>(x: [E; T]) -> [E; T] {
+    match T {
+        2 | 3 => {
+            let sum = sum::<F, E>(&x);
+            x.map(|e| e + sum.clone())
+        }
+        4 => {
+            let y = m4::<F, E>(&x);
+            array::from_fn(|i| y[i].clone())
+        }
+        _ if T % 4 == 0 => {
+            let blocks = x.chunks(4).map(m4::<F, E>).collect::<Vec<_>>();
+            let sums: [E; 4] = array::from_fn(|j| {
+                blocks[1..]
+                    .iter()
+                    .fold(blocks[0][j].clone(), |acc, block| acc + block[j].clone())
+            });
+            array::from_fn(|i| blocks[i / 4][i % 4].clone() + sums[i % 4].clone())
+        }
+        _ => panic!(
+            "Poseidon2 is defined for widths 2, 3 and multiples of 4, not {}",
+            T
+        ),
+    }
+}
+
+fn internal_matrix<F: PrimeField, E: Term<F>, const T: usize>(
+    x: [E; T],
+    diagonal: &[F; T],
+) -> [E; T] {
+    let sum = sum::<F, E>(&x);
+    array::from_fn(|i| x[i].clone() * diagonal[i] + sum.clone())
+}
+
+fn full_round<F: PrimeField, E: Term<F>, const T: usize>(x: [E; T], constants: [E; T]) -> [E; T] {
+    let y = array::from_fn(|i| sbox::<F, E>(x[i].clone() + constants[i].clone()));
+    external_matrix::<F, E, T>(y)
+}
+
+fn partial_round<F: PrimeField, E: Term<F>, const T: usize>(
+    mut x: [E; T],
+    constant: E,
+    diagonal: &[F; T],
+) -> [E; T] {
+    x[0] = sbox::<F, E>(x[0].clone() + constant);
+    internal_matrix(x, diagonal)
+}
+
+/// Native Poseidon2, the reference the chip is checked against.
+#[derive(Clone, Debug)]
+pub struct Poseidon2<F: PrimeField, const T: usize> {
+    full_rounds: usize,
+    partial_rounds: usize,
+    round_constants: Vec<[F; T]>,
+    internal_diagonal: [F; T],
+}
+
+impl<F: PrimeField, const T: usize> Poseidon2<F, T> {
+    pub fn new<S: Spec<F, T>>() -> Self {
+        assert_eq!(S::partial_rounds() % 2, 0);
+        Poseidon2 {
+            full_rounds: S::full_rounds(),
+            partial_rounds: S::partial_rounds(),
+            round_constants: S::round_constants(),
+            internal_diagonal: S::internal_diagonal(),
+        }
+    }
+
+    fn is_partial(&self, round: usize) -> bool {
+        (self.full_rounds / 2..self.full_rounds / 2 + self.partial_rounds).contains(&round)
+    }
+
+    pub fn permute(&self, state: [F; T]) -> [F; T] {
+        let mut state = external_matrix::<F, F, T>(state);
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            state = if self.is_partial(round) {
+                partial_round(state, constants[0], &self.internal_diagonal)
+            } else {
+                full_round(state, *constants)
+            };
+        }
+        state
+    }
+
+    /// Sponge with the last element as capacity, initialised to `L << 64` for domain
+    /// separation as halo2's `ConstantLength<L>`, and the last chunk padded with zeros.
+    pub fn hash<const L: usize>(&self, message: [F; L]) -> F {
+        let mut state = [F::ZERO; T];
+        state[T - 1] = F::from_u128((L as u128) << 64);
+        for chunk in message.chunks(T - 1) {
+            for (word, m) in state.iter_mut().zip(chunk) {
+                *word += m;
+            }
+            state = self.permute(state);
+        }
+        state[0]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Poseidon2Config<F: PrimeField, const T: usize> {
+    state: [Column<Advice>; T],
+    partial_sbox: Column<Advice>,
+    round_constants: [Column<Fixed>; T],
+    s_first: Selector,
+    s_full: Selector,
+    s_partial: Selector,
+    s_absorb: Selector,
+    poseidon2: Poseidon2<F, T>,
+}
+
+pub struct Poseidon2Chip<F: PrimeField, const T: usize> {
+    config: Poseidon2Config<F, T>,
+}
+
+impl<F: PrimeField, const T: usize> Chip<F> for Poseidon2Chip<F, T> {
+    type Config = Poseidon2Config<F, T>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeField, const T: usize> Poseidon2Chip<F, T> {
+    /// The first full round also applies the initial external matrix. A partial round row
+    /// keeps the S-box output of its first round in `partial_sbox`, so its constraints stay of
+    /// degree 5.
+    pub fn configure<S: Spec<F, T>>(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; T],
+        partial_sbox: Column<Advice>,
+        round_constants: [Column<Fixed>; T],
+    ) -> Poseidon2Config<F, T> {
+        let poseidon2 = Poseidon2::new::<S>();
+        state.map(|column| meta.enable_equality(column));
+
+        let s_first = meta.selector();
+        let s_full = meta.selector();
+        let s_partial = meta.selector();
+        let s_absorb = meta.selector();
+
+        let constrain =
+            |selector: Expression<F>, next: [Expression<F>; T], out: [Expression<F>; T]| {
+                next.into_iter()
+                    .zip(out)
+                    .map(|(next, out)| selector.clone() * (next - out))
+                    .collect::<Vec<_>>()
+            };
+
+        meta.create_gate("poseidon2 first full round", |meta| {
+            let s = meta.query_selector(s_first);
+            let cur = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let constants = round_constants.map(|column| meta.query_fixed(column, Rotation::cur()));
+
+            let out = full_round::<F, _, T>(external_matrix::<F, _, T>(cur), constants);
+            constrain(s, next, out)
+        });
+
+        meta.create_gate("poseidon2 full round", |meta| {
+            let s = meta.query_selector(s_full);
+            let cur = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let constants = round_constants.map(|column| meta.query_fixed(column, Rotation::cur()));
+
+            constrain(s, next, full_round::<F, _, T>(cur, constants))
+        });
+
+        let diagonal = poseidon2.internal_diagonal;
+        meta.create_gate("poseidon2 partial rounds", |meta| {
+            let s = meta.query_selector(s_partial);
+            let mut cur = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let sbox_out = meta.query_advice(partial_sbox, Rotation::cur());
+            let constant_a = meta.query_fixed(round_constants[0], Rotation::cur());
+            let constant_b = meta.query_fixed(round_constants[1], Rotation::cur());
+
+            let sbox_constraint =
+                s.clone() * (sbox_out.clone() - sbox::<F, _>(cur[0].clone() + constant_a));
+            cur[0] = sbox_out;
+            let mid = internal_matrix(cur, &diagonal);
+            let out = partial_round(mid, constant_b, &diagonal);
+
+            let mut constraints = constrain(s, next, out);
+            constraints.push(sbox_constraint);
+            constraints
+        });
+
+        // the message chunk is in the row below the state, the sum in the one after
+        meta.create_gate("poseidon2 absorb", |meta| {
+            let s = meta.query_selector(s_absorb);
+            state
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let cur = meta.query_advice(*column, Rotation::cur());
+                    let sum = meta.query_advice(*column, Rotation(2));
+                    if i < T - 1 {
+                        let chunk = meta.query_advice(*column, Rotation::next());
+                        s.clone() * (sum - cur - chunk)
+                    } else {
+                        s.clone() * (sum - cur)
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Poseidon2Config {
+            state,
+            partial_sbox,
+            round_constants,
+            s_first,
+            s_full,
+            s_partial,
+            s_absorb,
+            poseidon2,
+        }
+    }
+
+    pub fn construct(config: Poseidon2Config<F, T>) -> Self {
+        Poseidon2Chip { config }
+    }
+
+    /// `Poseidon2::hash` of a non-empty `message`, in one region.
+    pub fn hash<const L: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: [AssignedCell<F, F>; L],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let rate = T - 1;
+
+        layouter.assign_region(
+            || "poseidon2 hash",
+            |mut region| {
+                let mut offset = 0;
+                let mut state = Value::known([F::ZERO; T]);
+                let mut cells = vec![];
+
+                for (i, chunk) in message.chunks(rate).enumerate() {
+                    if i == 0 {
+                        // absorbing into the zero rate is a copy
+                        self.assign_chunk(&mut region, offset, chunk)?;
+                        let capacity = F::from_u128((L as u128) << 64);
+                        region.assign_advice_from_constant(
+                            || "capacity",
+                            config.state[T - 1],
+                            offset,
+                            capacity,
+                        )?;
+                        state = state.zip(values(chunk)).map(|(mut state, chunk)| {
+                            state[..chunk.len()].copy_from_slice(&chunk);
+                            state[T - 1] = capacity;
+                            state
+                        });
+                    } else {
+                        config.s_absorb.enable(&mut region, offset)?;
+                        self.assign_chunk(&mut region, offset + 1, chunk)?;
+                        state = state.zip(values(chunk)).map(|(mut state, chunk)| {
+                            for (word, m) in state.iter_mut().zip(chunk) {
+                                *word += m;
+                            }
+                            state
+                        });
+                        offset += 2;
+                        self.assign_state(&mut region, offset, state)?;
+                    }
+
+                    (offset, state, cells) = self.permute(&mut region, offset, state)?;
+                }
+
+                Ok(cells.swap_remove(0))
+            },
+        )
+    }
+
+    /// Copies `chunk` to the rate of row `offset`, padded with zeros.
+    fn assign_chunk(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        chunk: &[AssignedCell<F, F>],
+    ) -> Result<(), Error> {
+        for (i, column) in self.config.state[..T - 1].iter().enumerate() {
+            match chunk.get(i) {
+                Some(cell) => {
+                    cell.copy_advice(|| format!("message_{i}"), region, *column, offset)?;
+                }
+                None => {
+                    region.assign_advice_from_constant(
+                        || format!("padding_{i}"),
+                        *column,
+                        offset,
+                        F::ZERO,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: Value<[F; T]>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        self.config
+            .state
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                region.assign_advice(
+                    || format!("state_{i}"),
+                    *column,
+                    offset,
+                    || state.map(|state| state[i]),
+                )
+            })
+            .collect()
+    }
+
+    /// Rounds of the state assigned at `offset`, returning the row, value and cells of the
+    /// output.
+    #[allow(clippy::type_complexity)]
+    fn permute(
+        &self,
+        region: &mut Region<'_, F>,
+        mut offset: usize,
+        mut state: Value<[F; T]>,
+    ) -> Result<(usize, Value<[F; T]>, Vec<AssignedCell<F, F>>), Error> {
+        let config = &self.config;
+        let poseidon2 = &config.poseidon2;
+        let diagonal = poseidon2.internal_diagonal;
+        let rounds = &poseidon2.round_constants;
+
+        let mut cells = vec![];
+        let mut round = 0;
+        while round < rounds.len() {
+            if poseidon2.is_partial(round) {
+                let (constant_a, constant_b) = (rounds[round][0], rounds[round + 1][0]);
+                config.s_partial.enable(region, offset)?;
+                for (column, constant) in
+                    config.round_constants.iter().zip([constant_a, constant_b])
+                {
+                    region.assign_fixed(
+                        || "round constant",
+                        *column,
+                        offset,
+                        || Value::known(constant),
+                    )?;
+                }
+                region.assign_advice(
+                    || "partial sbox",
+                    config.partial_sbox,
+                    offset,
+                    || state.map(|state| sbox::<F, F>(state[0] + constant_a)),
+                )?;
+                state = state.map(|state| {
+                    let state = partial_round(state, constant_a, &diagonal);
+                    partial_round(state, constant_b, &diagonal)
+                });
+                round += 2;
+            } else {
+                let selector = if round == 0 {
+                    config.s_first
+                } else {
+                    config.s_full
+                };
+                selector.enable(region, offset)?;
+                for (column, constant) in config.round_constants.iter().zip(rounds[round]) {
+                    region.assign_fixed(
+                        || "round constant",
+                        *column,
+                        offset,
+                        || Value::known(constant),
+                    )?;
+                }
+                state = state.map(|state| {
+                    let state = if round == 0 {
+                        external_matrix::<F, F, T>(state)
+                    } else {
+                        state
+                    };
+                    full_round(state, rounds[round])
+                });
+                round += 1;
+            }
+
+            offset += 1;
+            cells = self.assign_state(region, offset, state)?;
+        }
+
+        Ok((offset, state, cells))
+    }
+}
+
+fn values<F: PrimeField>(cells: &[AssignedCell<F, F>]) -> Value<Vec<F>> {
+    cells.iter().fold(Value::known(vec![]), |acc, cell| {
+        acc.zip(cell.value()).map(|(mut values, value)| {
+            values.push(*value);
+            values
+        })
+    })
+}
+
+/// Proves that `instance` is the Poseidon2 digest of `message`, as `HashCircuit` does for
+/// Poseidon.
+#[derive(Clone, Copy)]
+pub struct Poseidon2HashCircuit<F, S, const T: usize, const L: usize>
+where
+    F: PrimeField,
+    S: Spec<F, T>,
+{
+    pub instance: F,
+    pub message: Value<[F; L]>,
+    pub _spec: PhantomData<S>,
+}
+
+impl<F, S, const T: usize, const L: usize> Poseidon2HashCircuit<F, S, T, L>
+where
+    F: PrimeField,
+    S: Spec<F, T>,
+{
+    /// Circuit for `message`, with the digest computed natively as its instance.
+    pub fn new(message: [F; L]) -> Self {
+        Self {
+            instance: Self::digest(message),
+            message: Value::known(message),
+            _spec: PhantomData,
+        }
+    }
+
+    pub fn digest(message: [F; L]) -> F {
+        Poseidon2::new::<S>().hash(message)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Poseidon2HashConfig<F: PrimeField, const T: usize> {
+    poseidon2: Poseidon2Config<F, T>,
+    instance: Column<Instance>,
+}
+
+impl<F, S, const T: usize, const L: usize> Circuit<F> for Poseidon2HashCircuit<F, S, T, L>
+where
+    F: PrimeField,
+    S: Spec<F, T>,
+{
+    type Config = Poseidon2HashConfig<F, T>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            instance: F::ZERO,
+            message: Value::unknown(),
+            _spec: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let state = [(); T].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let round_constants = [(); T].map(|_| meta.fixed_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        meta.enable_constant(round_constants[0]);
+
+        Poseidon2HashConfig {
+            poseidon2: Poseidon2Chip::configure::<S>(meta, state, partial_sbox, round_constants),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let state = config.poseidon2.state;
+        let message = layouter.assign_region(
+            || "load message",
+            |mut region| {
+                let message = (0..L)
+                    .map(|i| {
+                        region.assign_advice(
+                            || format!("load message_{i}"),
+                            state[i % T],
+                            i / T,
+                            || self.message.map(|message| message[i]),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(message.try_into().unwrap())
+            },
+        )?;
+
+        let chip = Poseidon2Chip::construct(config.poseidon2);
+        let digest = chip.hash(layouter.namespace(|| "hash"), message)?;
+        layouter.constrain_instance(digest.cell(), config.instance, 0)
+    }
+}
+
+impl<F, S, const T: usize, const L: usize> CircuitExt<F> for Poseidon2HashCircuit<F, S, T, L>
+where
+    F: PrimeField,
+    S: Spec<F, T>,
+{
+    fn num_instance(&self) -> Vec<usize> {
+        vec![1]
+    }
+
+    fn instances(&self) -> Vec<Vec<F>> {
+        vec![vec![self.instance]]
+    }
+}
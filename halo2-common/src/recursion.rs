@@ -0,0 +1,616 @@
+// Based upon https://github.com/privacy-scaling-explorations/snark-verifier/blob/main/snark-verifier/examples/recursion.rs
+//! Incremental Poseidon hash chain, each step proving `state = H^10(previous state)` over a
+//! 4-element state, like the Nova and plonky2 chains, and verifying the previous step's proof
+//! by accumulating it into the next one.
+use ff::Field;
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error, ProvingKey, VerifyingKey},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use halo2_wrong_ecc::{
+    integer::rns::Rns,
+    maingate::{
+        MainGate, MainGateColumn, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig,
+        RangeInstructions, RegionCtx,
+    },
+    EccConfig,
+};
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::{self, native::NativeLoader, Loader},
+    pcs::{
+        kzg::{KzgAccumulator, KzgDecidingKey, KzgSuccinctVerifyingKey, LimbsEncoding},
+        AccumulationScheme, AccumulationSchemeProver,
+    },
+    system::halo2::{compile, Config},
+    util::{
+        arithmetic::{fe_to_fe, fe_to_limbs},
+        hash,
+    },
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
+};
+use snark_verifier_sdk::{
+    halo2::{gen_dummy_snark, gen_snark_gwc, PoseidonTranscript, POSEIDON_SPEC},
+    CircuitExt, Snark, SnarkWitness, GWC,
+};
+use std::rc::Rc;
+
+use crate::keystore::{self, KeyStore};
+
+pub(crate) const LIMBS: usize = 4;
+pub(crate) const BITS: usize = 68;
+const T: usize = 5;
+const RATE: usize = 4;
+
+/// Number of elements in the chain's state.
+pub const WIDTH: usize = 4;
+/// Number of hashes of the state each step proves.
+pub const HASHES_PER_STEP: usize = 10;
+
+/// Degree of the SRS the step circuit needs.
+pub const K: u32 = 22;
+
+pub(crate) type Svk = KzgSuccinctVerifyingKey<G1Affine>;
+pub(crate) type PlonkSuccinctVerifier =
+    verifier::plonk::PlonkSuccinctVerifier<GWC, LimbsEncoding<LIMBS, BITS>>;
+pub(crate) type PlonkVerifier = verifier::plonk::PlonkVerifier<GWC, LimbsEncoding<LIMBS, BITS>>;
+type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+pub(crate) type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+
+/// Poseidon with the transcript's spec, natively or in-circuit depending on `loader`.
+fn poseidon<L: Loader<G1Affine>>(loader: &L, inputs: &[L::LoadedScalar]) -> L::LoadedScalar {
+    let mut hasher = hash::Poseidon::<Fr, _, T, RATE>::from_spec(loader, POSEIDON_SPEC.clone());
+    hasher.update(inputs);
+    hasher.squeeze()
+}
+
+/// Hashes a state into the next like circomlib's `PoseidonEx(4, 4)`: one sponge absorbing the
+/// state, each output squeezed after absorbing the one before.
+fn poseidon_ex<L: Loader<G1Affine>>(
+    loader: &L,
+    state: &[L::LoadedScalar; WIDTH],
+) -> [L::LoadedScalar; WIDTH] {
+    let mut hasher = hash::Poseidon::<Fr, _, T, RATE>::from_spec(loader, POSEIDON_SPEC.clone());
+    hasher.update(state);
+    std::array::from_fn(|_| {
+        let output = hasher.squeeze();
+        hasher.update(&[output.clone()]);
+        output
+    })
+}
+
+/// One step of the chain, `HASHES_PER_STEP` hashes of `state`.
+fn step<L: Loader<G1Affine>>(
+    loader: &L,
+    state: [L::LoadedScalar; WIDTH],
+) -> [L::LoadedScalar; WIDTH] {
+    (0..HASHES_PER_STEP).fold(state, |state, _| poseidon_ex(loader, &state))
+}
+
+/// Native value of the chain after `num_steps` steps from `initial_state`.
+pub fn hash_chain(initial_state: [Fr; WIDTH], num_steps: usize) -> [Fr; WIDTH] {
+    (0..num_steps).fold(initial_state, |state, _| step(&NativeLoader, state))
+}
+
+/// Digest of a protocol's preprocessed commitments, identifying the step circuit.
+fn preprocessed_digest(protocol: &PlonkProtocol<G1Affine>) -> Fr {
+    let inputs = protocol
+        .preprocessed
+        .iter()
+        .flat_map(|preprocessed| [preprocessed.x, preprocessed.y])
+        .map(fe_to_fe)
+        .chain(protocol.transcript_initial_state)
+        .collect::<Vec<_>>();
+    poseidon(&NativeLoader, &inputs)
+}
+
+fn succinct_verify<'a>(
+    svk: &Svk,
+    loader: &Rc<Halo2Loader<'a>>,
+    snark: &SnarkWitness,
+    preprocessed_digest: AssignedCell<Fr, Fr>,
+) -> (
+    Vec<Vec<AssignedCell<Fr, Fr>>>,
+    Vec<KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>>,
+) {
+    // the previous proof has to be one of this circuit, so its preprocessed commitments are
+    // witnessed and bound to the digest carried from step to step
+    let preprocessed_digest = loader.scalar_from_assigned(preprocessed_digest);
+    let protocol = snark.protocol.loaded_preprocessed_as_witness(loader);
+    let inputs = protocol
+        .preprocessed
+        .iter()
+        .flat_map(|preprocessed| {
+            let assigned = preprocessed.assigned();
+            [assigned.x(), assigned.y()]
+                .map(|coordinate| loader.scalar_from_assigned(coordinate.native().clone()))
+        })
+        .chain(protocol.transcript_initial_state.clone())
+        .collect::<Vec<_>>();
+    loader
+        .assert_eq("", &poseidon(loader, &inputs), &preprocessed_digest)
+        .unwrap();
+
+    let instances = snark
+        .instances
+        .iter()
+        .map(|instances| {
+            instances
+                .iter()
+                .map(|instance| loader.assign_scalar(*instance))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::from_spec(
+        loader,
+        snark.proof(),
+        POSEIDON_SPEC.clone(),
+    );
+    let proof =
+        PlonkSuccinctVerifier::read_proof(svk, &protocol, &instances, &mut transcript).unwrap();
+    let accumulators = PlonkSuccinctVerifier::verify(svk, &protocol, &instances, &proof).unwrap();
+
+    (
+        instances
+            .into_iter()
+            .map(|instances| {
+                instances
+                    .into_iter()
+                    .map(|instance| instance.into_assigned())
+                    .collect()
+            })
+            .collect(),
+        accumulators,
+    )
+}
+
+fn select_accumulator<'a>(
+    loader: &Rc<Halo2Loader<'a>>,
+    condition: &AssignedCell<Fr, Fr>,
+    lhs: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+    rhs: &KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+) -> Result<KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>, Error> {
+    let [lhs, rhs]: [_; 2] = [lhs.lhs.assigned(), lhs.rhs.assigned()]
+        .iter()
+        .zip([rhs.lhs.assigned(), rhs.rhs.assigned()].iter())
+        .map(|(lhs, rhs)| {
+            loader
+                .ecc_chip()
+                .select(&mut loader.ctx_mut(), condition, lhs, rhs)
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .try_into()
+        .unwrap();
+    Ok(KzgAccumulator::new(
+        loader.ec_point_from_assigned(lhs),
+        loader.ec_point_from_assigned(rhs),
+    ))
+}
+
+pub(crate) fn accumulate<'a>(
+    loader: &Rc<Halo2Loader<'a>>,
+    accumulators: Vec<KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>>,
+    as_proof: Value<&'_ [u8]>,
+) -> KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>> {
+    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::from_spec(
+        loader,
+        as_proof,
+        POSEIDON_SPEC.clone(),
+    );
+    let proof = GWC::read_proof(&Default::default(), &accumulators, &mut transcript).unwrap();
+    GWC::verify(&Default::default(), &accumulators, &proof).unwrap()
+}
+
+#[derive(Clone)]
+pub struct RecursionConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl RecursionConfig {
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        composition_bits: Vec<usize>,
+        overflow_bits: Vec<usize>,
+    ) -> Self {
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+        let range_config =
+            RangeChip::<Fr>::configure(meta, &main_gate_config, composition_bits, overflow_bits);
+        RecursionConfig {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    pub fn main_gate(&self) -> MainGate<Fr> {
+        MainGate::new(self.main_gate_config.clone())
+    }
+
+    pub fn range_chip(&self) -> RangeChip<Fr> {
+        RangeChip::new(self.range_config.clone())
+    }
+
+    pub fn ecc_chip(&self) -> BaseFieldEccChip {
+        BaseFieldEccChip::new(EccConfig::new(
+            self.range_config.clone(),
+            self.main_gate_config.clone(),
+        ))
+    }
+}
+
+/// One step of the chain. Its instances are the limbs of the accumulator, followed by the
+/// digest of its own verifying key, the initial state, the current state and the round.
+#[derive(Clone)]
+pub struct HashChainCircuit {
+    svk: Svk,
+    default_accumulator: KzgAccumulator<G1Affine, NativeLoader>,
+    previous: SnarkWitness,
+    round: usize,
+    instances: Vec<Fr>,
+    as_proof: Value<Vec<u8>>,
+}
+
+impl HashChainCircuit {
+    pub const PREPROCESSED_DIGEST_ROW: usize = 4 * LIMBS;
+    /// First of the `WIDTH` rows of the initial state.
+    pub const INITIAL_STATE_ROW: usize = Self::PREPROCESSED_DIGEST_ROW + 1;
+    /// First of the `WIDTH` rows of the current state.
+    pub const STATE_ROW: usize = Self::INITIAL_STATE_ROW + WIDTH;
+    pub const ROUND_ROW: usize = Self::STATE_ROW + WIDTH;
+    pub const NUM_INSTANCE: usize = Self::ROUND_ROW + 1;
+
+    /// Step `round`, hashing the state of `previous` (or `initial_state` in the first round)
+    /// into `state`.
+    pub fn new(
+        params: &ParamsKZG<Bn256>,
+        previous: Snark,
+        initial_state: [Fr; WIDTH],
+        state: [Fr; WIDTH],
+        round: usize,
+    ) -> Self {
+        let svk = params.get_g()[0].into();
+        let default_accumulator = KzgAccumulator::new(params.get_g()[1], params.get_g()[0]);
+
+        let accumulators = if round > 0 {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::from_spec(
+                previous.proof.as_slice(),
+                POSEIDON_SPEC.clone(),
+            );
+            let proof = PlonkSuccinctVerifier::read_proof(
+                &svk,
+                &previous.protocol,
+                &previous.instances,
+                &mut transcript,
+            )
+            .unwrap();
+            PlonkSuccinctVerifier::verify(&svk, &previous.protocol, &previous.instances, &proof)
+                .unwrap()
+        } else {
+            let num_accumulator = 1 + previous.protocol.accumulator_indices.len();
+            vec![default_accumulator.clone(); num_accumulator]
+        };
+
+        let (accumulator, as_proof) = {
+            let mut transcript =
+                PoseidonTranscript::<NativeLoader, _>::from_spec(vec![], POSEIDON_SPEC.clone());
+            let accumulator =
+                GWC::create_proof(&Default::default(), &accumulators, &mut transcript, OsRng)
+                    .unwrap();
+            (accumulator, transcript.finalize())
+        };
+
+        let preprocessed_digest = preprocessed_digest(&previous.protocol);
+        let instances = [
+            accumulator.lhs.x,
+            accumulator.lhs.y,
+            accumulator.rhs.x,
+            accumulator.rhs.y,
+        ]
+        .into_iter()
+        .flat_map(fe_to_limbs::<_, _, LIMBS, BITS>)
+        .chain([preprocessed_digest])
+        .chain(initial_state)
+        .chain(state)
+        .chain([Fr::from(round as u64)])
+        .collect();
+
+        Self {
+            svk,
+            default_accumulator,
+            previous: previous.into(),
+            round,
+            instances,
+            as_proof: Value::known(as_proof),
+        }
+    }
+
+    /// Stand-in for the proof preceding the first round, whose accumulators and state the
+    /// first round ignores.
+    pub fn initial_snark(params: &ParamsKZG<Bn256>, vk: Option<&VerifyingKey<G1Affine>>) -> Snark {
+        let mut snark =
+            gen_dummy_snark::<HashChainCircuit, GWC>(params, vk, vec![Self::NUM_INSTANCE]);
+        let g = params.get_g();
+        snark.instances = vec![[g[1].x, g[1].y, g[0].x, g[0].y]
+            .into_iter()
+            .flat_map(fe_to_limbs::<_, _, LIMBS, BITS>)
+            .chain([Fr::ZERO; Self::NUM_INSTANCE - 4 * LIMBS])
+            .collect()];
+        snark
+    }
+
+    /// Initial state exposed by a step's snark.
+    pub fn initial_state(snark: &Snark) -> [Fr; WIDTH] {
+        snark.instances[0][Self::INITIAL_STATE_ROW..Self::STATE_ROW]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Current state exposed by a step's snark.
+    pub fn state(snark: &Snark) -> [Fr; WIDTH] {
+        snark.instances[0][Self::STATE_ROW..Self::ROUND_ROW]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Zero-based round of a step's snark.
+    pub fn round(snark: &Snark) -> Fr {
+        snark.instances[0][Self::ROUND_ROW]
+    }
+
+    fn as_proof(&self) -> Value<&[u8]> {
+        self.as_proof.as_ref().map(Vec::as_slice)
+    }
+
+    fn load_default_accumulator<'a>(
+        &self,
+        loader: &Rc<Halo2Loader<'a>>,
+    ) -> Result<KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>, Error> {
+        let [lhs, rhs] =
+            [self.default_accumulator.lhs, self.default_accumulator.rhs].map(|default| {
+                let assigned = loader
+                    .ecc_chip()
+                    .assign_constant(&mut loader.ctx_mut(), default)
+                    .unwrap();
+                loader.ec_point_from_assigned(assigned)
+            });
+        Ok(KzgAccumulator::new(lhs, rhs))
+    }
+}
+
+impl Circuit<Fr> for HashChainCircuit {
+    type Config = RecursionConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            default_accumulator: self.default_accumulator.clone(),
+            previous: self.previous.without_witnesses(),
+            round: self.round,
+            instances: self.instances.clone(),
+            as_proof: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        RecursionConfig::configure(
+            meta,
+            vec![BITS / LIMBS],
+            Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = config.main_gate();
+        let range_chip = config.range_chip();
+
+        range_chip.load_table(&mut layouter)?;
+
+        let (accumulator_limbs, assigned) = layouter.assign_region(
+            || "",
+            |region| {
+                let mut ctx = RegionCtx::new(region, 0);
+
+                // the instances after the accumulator, indexed by row
+                let assigned = self.instances[Self::PREPROCESSED_DIGEST_ROW..]
+                    .iter()
+                    .map(|instance| {
+                        main_gate.assign_to_column(
+                            &mut ctx,
+                            Value::known(*instance),
+                            MainGateColumn::A,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let row = |row: usize| &assigned[row - Self::PREPROCESSED_DIGEST_ROW];
+                let preprocessed_digest = row(Self::PREPROCESSED_DIGEST_ROW);
+                let round = row(Self::ROUND_ROW);
+                let first_round = main_gate.is_zero(&mut ctx, round)?;
+                let not_first_round = main_gate.not(&mut ctx, &first_round)?;
+
+                let loader = Halo2Loader::new(config.ecc_chip(), ctx);
+                let (mut previous_instances, previous_accumulators) = succinct_verify(
+                    &self.svk,
+                    &loader,
+                    &self.previous,
+                    preprocessed_digest.clone(),
+                );
+
+                let default_accumulator = self.load_default_accumulator(&loader)?;
+                let previous_accumulators = previous_accumulators
+                    .iter()
+                    .map(|previous_accumulator| {
+                        select_accumulator(
+                            &loader,
+                            &first_round,
+                            &default_accumulator,
+                            previous_accumulator,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                let KzgAccumulator { lhs, rhs } =
+                    accumulate(&loader, previous_accumulators, self.as_proof());
+
+                let lhs = lhs.into_assigned();
+                let rhs = rhs.into_assigned();
+                let previous_instances = previous_instances.pop().unwrap();
+
+                // the step itself, hashing the initial state in the first round and the
+                // previous state afterwards
+                let previous_state = std::array::from_fn(|idx| {
+                    let selected = main_gate
+                        .select(
+                            &mut loader.ctx_mut(),
+                            row(Self::INITIAL_STATE_ROW + idx),
+                            &previous_instances[Self::STATE_ROW + idx],
+                            &first_round,
+                        )
+                        .unwrap();
+                    loader.scalar_from_assigned(selected)
+                });
+                let next_state = step(&loader, previous_state).map(|next| next.into_assigned());
+
+                let mut ctx = loader.ctx_mut();
+                // Propagate preprocessed_digest and initial_state
+                for idx in Self::PREPROCESSED_DIGEST_ROW..Self::STATE_ROW {
+                    let propagated = main_gate.mul(&mut ctx, row(idx), &not_first_round)?;
+                    ctx.constrain_equal(propagated.cell(), previous_instances[idx].cell())?;
+                }
+                // Verify the current state is the hashes of the previous one
+                for (idx, next) in next_state.iter().enumerate() {
+                    ctx.constrain_equal(row(Self::STATE_ROW + idx).cell(), next.cell())?;
+                }
+                // Verify round is increased by 1 when not at first round
+                let next_round = main_gate.add(
+                    &mut ctx,
+                    &not_first_round,
+                    &previous_instances[Self::ROUND_ROW],
+                )?;
+                ctx.constrain_equal(round.cell(), next_round.cell())?;
+
+                let accumulator_limbs = [lhs.x(), lhs.y(), rhs.x(), rhs.y()]
+                    .into_iter()
+                    .flat_map(|coordinate| coordinate.limbs())
+                    .map(|limb| limb.as_ref().clone())
+                    .collect::<Vec<_>>();
+
+                Ok((accumulator_limbs, assigned))
+            },
+        )?;
+
+        for (row, limb) in accumulator_limbs.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| ""), limb, row)?;
+        }
+        for (row, value) in assigned.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| ""), value, 4 * LIMBS + row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CircuitExt<Fr> for HashChainCircuit {
+    fn num_instance(&self) -> Vec<usize> {
+        vec![Self::NUM_INSTANCE]
+    }
+
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    fn accumulator_indices() -> Option<Vec<(usize, usize)>> {
+        Some((0..4 * LIMBS).map(|idx| (0, idx)).collect())
+    }
+}
+
+/// Proving key of the step circuit, the same for every round.
+pub fn gen_recursion_pk(
+    params: &ParamsKZG<Bn256>,
+    store: &KeyStore,
+) -> Result<ProvingKey<G1Affine>, keystore::Error> {
+    let circuit = HashChainCircuit::new(
+        params,
+        HashChainCircuit::initial_snark(params, None),
+        [Fr::ZERO; WIDTH],
+        [Fr::ZERO; WIDTH],
+        0,
+    );
+    store.pk(params, &circuit)
+}
+
+/// Proves round `round` of the chain on top of `previous`, the snark of the round before or
+/// `HashChainCircuit::initial_snark` for the first, returning the new state and snark.
+pub fn gen_recursion_step(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    previous: Snark,
+    initial_state: [Fr; WIDTH],
+    round: usize,
+) -> ([Fr; WIDTH], Snark) {
+    let state = if round == 0 {
+        initial_state
+    } else {
+        HashChainCircuit::state(&previous)
+    };
+    let state = hash_chain(state, 1);
+    let circuit = HashChainCircuit::new(params, previous, initial_state, state, round);
+    (state, gen_snark_gwc(params, pk, circuit, None::<&str>))
+}
+
+/// Proves `num_steps` rounds of the chain, returning the final state and the last round's
+/// snark.
+pub fn gen_recursion_snark(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    initial_state: [Fr; WIDTH],
+    num_steps: usize,
+) -> ([Fr; WIDTH], Snark) {
+    let mut state = initial_state;
+    let mut previous = HashChainCircuit::initial_snark(params, Some(pk.get_vk()));
+    for round in 0..num_steps {
+        (state, previous) = gen_recursion_step(params, pk, previous, initial_state, round);
+    }
+    (state, previous)
+}
+
+/// Verifies the last round's snark against `vk`, including the deferred pairing check of
+/// the accumulator it carries.
+pub fn verify_recursion_snark(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    snark: &Snark,
+) -> bool {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg()
+            .with_num_instance(vec![HashChainCircuit::NUM_INSTANCE])
+            .with_accumulator_indices(HashChainCircuit::accumulator_indices()),
+    );
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    // every round checked its predecessor against this digest, so it has to be our own
+    if snark.instances[0][HashChainCircuit::PREPROCESSED_DIGEST_ROW]
+        != preprocessed_digest(&protocol)
+    {
+        return false;
+    }
+
+    let mut transcript = PoseidonTranscript::<NativeLoader, _>::from_spec(
+        snark.proof.as_slice(),
+        POSEIDON_SPEC.clone(),
+    );
+    match PlonkVerifier::read_proof(&dk, &protocol, &snark.instances, &mut transcript) {
+        Ok(proof) => PlonkVerifier::verify(&dk, &protocol, &snark.instances, &proof).is_ok(),
+        Err(_) => false,
+    }
+}
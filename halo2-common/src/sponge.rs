@@ -0,0 +1,359 @@
+//! Poseidon sponge over messages of any length up to `MAX`, the length being a witness.
+//!
+//! The message is padded with `1` and then zeros to a multiple of the rate, which makes the
+//! padding injective, and the capacity starts at `1`, distinct from halo2's `ConstantLength<L>`
+//! domains whose capacity is `L * 2^64`. The circuit always runs the permutations of a `MAX`
+//! long message and outputs the state after the chunk holding the `1`, so its digest is the one
+//! of the unpadded message, independent of `MAX`.
+use std::array;
+use std::marker::PhantomData;
+
+use ff::Field;
+use halo2_gadgets::poseidon::{
+    primitives::Spec, PoseidonInstructions, Pow5Chip, Pow5Config, StateWord,
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+use snark_verifier_sdk::CircuitExt;
+
+/// Initial capacity element of the variable length domain.
+pub fn domain<F: Field>() -> F {
+    F::ONE
+}
+
+/// Native Poseidon permutation, as `Pow5Chip` constrains it.
+pub fn permute<F, S, const WIDTH: usize, const RATE: usize>(state: &mut [F; WIDTH])
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE>,
+{
+    let (round_constants, mds, _) = S::constants();
+    let half_full_rounds = S::full_rounds() / 2;
+    let partial = half_full_rounds..half_full_rounds + S::partial_rounds();
+
+    for (round, constants) in round_constants.iter().enumerate() {
+        for (word, constant) in state.iter_mut().zip(constants) {
+            *word += constant;
+        }
+        if partial.contains(&round) {
+            state[0] = S::sbox(state[0]);
+        } else {
+            for word in state.iter_mut() {
+                *word = S::sbox(*word);
+            }
+        }
+        *state = array::from_fn(|i| {
+            mds[i]
+                .iter()
+                .zip(state.iter())
+                .fold(F::ZERO, |acc, (m, word)| acc + *m * word)
+        });
+    }
+}
+
+/// `message` padded with `1` and zeros to a multiple of `rate`.
+fn pad<F: Field>(message: &[F], rate: usize) -> Vec<F> {
+    let mut padded = message.to_vec();
+    padded.push(F::ONE);
+    padded.resize(padded.len().next_multiple_of(rate), F::ZERO);
+    padded
+}
+
+/// Native digest of `message`, as constrained by `VariableLengthHashCircuit`.
+pub fn hash<F, S, const WIDTH: usize, const RATE: usize>(message: &[F]) -> F
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE>,
+{
+    let mut state = [F::ZERO; WIDTH];
+    state[RATE] = domain();
+    for chunk in pad(message, RATE).chunks(RATE) {
+        for (word, m) in state.iter_mut().zip(chunk) {
+            *word += m;
+        }
+        permute::<F, S, WIDTH, RATE>(&mut state);
+    }
+    state[0]
+}
+
+/// Proves that `instance` is the Poseidon digest of a message of at most `MAX` elements.
+#[derive(Clone)]
+pub struct VariableLengthHashCircuit<F, S, const WIDTH: usize, const RATE: usize, const MAX: usize>
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Clone,
+{
+    pub instance: F,
+    pub message: Value<Vec<F>>,
+    pub _spec: PhantomData<S>,
+}
+
+impl<F, S, const WIDTH: usize, const RATE: usize, const MAX: usize>
+    VariableLengthHashCircuit<F, S, WIDTH, RATE, MAX>
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Clone,
+{
+    /// Circuit for `message`, with the digest computed natively as its instance.
+    pub fn new(message: Vec<F>) -> Self {
+        assert!(message.len() <= MAX, "message longer than {}", MAX);
+        Self {
+            instance: hash::<F, S, WIDTH, RATE>(&message),
+            message: Value::known(message),
+            _spec: PhantomData,
+        }
+    }
+
+    /// Permutations of a `MAX` long message, the `1` of the padding included.
+    const NUM_CHUNKS: usize = MAX / RATE + 1;
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableLengthHashConfig<F: Field, const WIDTH: usize, const RATE: usize> {
+    advice: [Column<Advice>; 4],
+    instance: Column<Instance>,
+    s_pad: Selector,
+    s_add: Selector,
+    s_select: Selector,
+    poseidon_config: Pow5Config<F, WIDTH, RATE>,
+}
+
+impl<F, S, const WIDTH: usize, const RATE: usize, const MAX: usize> Circuit<F>
+    for VariableLengthHashCircuit<F, S, WIDTH, RATE, MAX>
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Clone,
+{
+    type Config = VariableLengthHashConfig<F, WIDTH, RATE>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "halo2_circuit_params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            instance: F::ZERO,
+            message: Value::unknown(),
+            _spec: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        advice.map(|column| meta.enable_equality(column));
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+        meta.enable_constant(rc_b[0]);
+
+        let [a, b, c, d] = advice;
+        let s_pad = meta.selector();
+        let s_add = meta.selector();
+        let s_select = meta.selector();
+
+        // one row per message position j: a = m_j, b = [j == len], c = [j >= len] and
+        // d = the padded word, m_j before the length, then 1, then zeros
+        meta.create_gate("variable length padding", |meta| {
+            let s = meta.query_selector(s_pad);
+            let message = meta.query_advice(a, Rotation::cur());
+            let is_end = meta.query_advice(b, Rotation::cur());
+            let ended = meta.query_advice(c, Rotation::cur());
+            let ended_prev = meta.query_advice(c, Rotation::prev());
+            let word = meta.query_advice(d, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                s.clone() * is_end.clone() * (one.clone() - is_end.clone()),
+                s.clone() * (ended.clone() - ended_prev - is_end.clone()),
+                s * (word - (one - ended) * message - is_end),
+            ]
+        });
+
+        meta.create_gate("absorb", |meta| {
+            let s = meta.query_selector(s_add);
+            let [state, word, sum] =
+                [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (sum - state - word)]
+        });
+
+        // one row per chunk k: a = digest after chunk k, c = [the padding 1 is in chunks 0..=k]
+        // and d accumulates the digest of the chunk holding the 1
+        meta.create_gate("select digest", |meta| {
+            let s = meta.query_selector(s_select);
+            let output = meta.query_advice(a, Rotation::cur());
+            let [ended, ended_prev] =
+                [Rotation::cur(), Rotation::prev()].map(|rotation| meta.query_advice(c, rotation));
+            let [acc, acc_prev] =
+                [Rotation::cur(), Rotation::prev()].map(|rotation| meta.query_advice(d, rotation));
+            vec![s * (acc - acc_prev - (ended - ended_prev) * output)]
+        });
+
+        VariableLengthHashConfig {
+            advice,
+            instance,
+            s_pad,
+            s_add,
+            s_select,
+            poseidon_config: Pow5Chip::configure::<S>(meta, state, partial_sbox, rc_a, rc_b),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let [a, b, c, d] = config.advice;
+        let len = self.message.as_ref().map(Vec::len);
+
+        // padded words of all chunks, and [j >= len] at the last position of each chunk
+        let (words, ended) = layouter.assign_region(
+            || "padding",
+            |mut region| {
+                region.assign_advice_from_constant(|| "not ended", c, 0, F::ZERO)?;
+
+                let mut words = Vec::with_capacity(Self::NUM_CHUNKS * RATE);
+                let mut ended = Vec::with_capacity(MAX + 1);
+                for j in 0..=MAX {
+                    let offset = j + 1;
+                    config.s_pad.enable(&mut region, offset)?;
+
+                    let message = self
+                        .message
+                        .as_ref()
+                        .map(|message| message.get(j).copied().unwrap_or(F::ZERO));
+                    let flag = |set: bool| if set { F::ONE } else { F::ZERO };
+                    region.assign_advice(|| format!("message_{j}"), a, offset, || message)?;
+                    region.assign_advice(
+                        || format!("is_end_{j}"),
+                        b,
+                        offset,
+                        || len.map(|len| flag(j == len)),
+                    )?;
+                    ended.push(region.assign_advice(
+                        || format!("ended_{j}"),
+                        c,
+                        offset,
+                        || len.map(|len| flag(j >= len)),
+                    )?);
+                    words.push(region.assign_advice(
+                        || format!("word_{j}"),
+                        d,
+                        offset,
+                        || {
+                            len.zip(message)
+                                .map(|(len, m)| if j < len { m } else { flag(j == len) })
+                        },
+                    )?);
+                }
+                // exactly one position is the end
+                region.constrain_constant(ended[MAX].cell(), F::ONE)?;
+
+                for j in MAX + 1..Self::NUM_CHUNKS * RATE {
+                    words.push(region.assign_advice_from_constant(
+                        || format!("word_{j}"),
+                        d,
+                        j + 1,
+                        F::ZERO,
+                    )?);
+                }
+                Ok((words, ended))
+            },
+        )?;
+
+        let chip = Pow5Chip::construct(config.poseidon_config.clone());
+        let mut state = words[..RATE].to_vec();
+        state.push(layouter.assign_region(
+            || "capacity",
+            |mut region| region.assign_advice_from_constant(|| "capacity", a, 0, domain::<F>()),
+        )?);
+
+        let mut outputs = Vec::with_capacity(Self::NUM_CHUNKS);
+        for (k, chunk) in words.chunks(RATE).enumerate() {
+            if k > 0 {
+                let sums = layouter.assign_region(
+                    || format!("absorb chunk {k}"),
+                    |mut region| {
+                        (0..RATE)
+                            .map(|i| {
+                                config.s_add.enable(&mut region, i)?;
+                                let word = state[i].copy_advice(|| "state", &mut region, a, i)?;
+                                let m = chunk[i].copy_advice(|| "word", &mut region, b, i)?;
+                                region.assign_advice(
+                                    || "sum",
+                                    c,
+                                    i,
+                                    || word.value().zip(m.value()).map(|(word, m)| *word + m),
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    },
+                )?;
+                state[..RATE].clone_from_slice(&sums);
+            }
+
+            let input: [StateWord<F>; WIDTH] = array::from_fn(|i| state[i].clone().into());
+            let permuted =
+                <Pow5Chip<F, WIDTH, RATE> as PoseidonInstructions<F, S, WIDTH, RATE>>::permute(
+                    &chip,
+                    &mut layouter.namespace(|| format!("permute chunk {k}")),
+                    &input,
+                )?;
+            state = permuted.into_iter().map(AssignedCell::from).collect();
+            outputs.push(state[0].clone());
+        }
+
+        let digest = layouter.assign_region(
+            || "select digest",
+            |mut region| {
+                region.assign_advice_from_constant(|| "not ended", c, 0, F::ZERO)?;
+                let mut acc = region.assign_advice_from_constant(|| "acc", d, 0, F::ZERO)?;
+                let mut ended_prev = Value::known(F::ZERO);
+
+                for (k, output) in outputs.iter().enumerate() {
+                    let offset = k + 1;
+                    config.s_select.enable(&mut region, offset)?;
+
+                    let output = output.copy_advice(|| "output", &mut region, a, offset)?;
+                    // last position of chunk k
+                    let last = ((k + 1) * RATE - 1).min(MAX);
+                    let ended_k = ended[last].copy_advice(|| "ended", &mut region, c, offset)?;
+
+                    let ended_k = ended_k.value().copied();
+                    let selected = (ended_k - ended_prev) * output.value().copied();
+                    acc = region.assign_advice(
+                        || "acc",
+                        d,
+                        offset,
+                        || acc.value().copied() + selected,
+                    )?;
+                    ended_prev = ended_k;
+                }
+                Ok(acc)
+            },
+        )?;
+
+        layouter.constrain_instance(digest.cell(), config.instance, 0)
+    }
+}
+
+impl<F, S, const WIDTH: usize, const RATE: usize, const MAX: usize> CircuitExt<F>
+    for VariableLengthHashCircuit<F, S, WIDTH, RATE, MAX>
+where
+    F: Field,
+    S: Spec<F, WIDTH, RATE> + Clone,
+{
+    fn num_instance(&self) -> Vec<usize> {
+        vec![1]
+    }
+
+    fn instances(&self) -> Vec<Vec<F>> {
+        vec![vec![self.instance]]
+    }
+}
@@ -0,0 +1,226 @@
+//! Aggregation trees, for more snarks than a single `AggregationCircuit` can verify. Leaves are
+//! aggregated in batches of `fan_out`, the resulting aggregation snarks in batches of `fan_out`
+//! again, and so on up to a single root. Each node forwards the instances of its leaves, so the
+//! root exposes the instances of every leaf in order.
+use std::fmt;
+
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::{
+    plonk::{ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rayon::prelude::*;
+use snark_verifier_sdk::Snark;
+
+use crate::aggregation::{
+    downsize, gen_aggregation_pk, gen_aggregation_snark, verify_aggregation_snark,
+    AggregationCircuit, SnarkInstances,
+};
+use crate::keystore::{self, KeyStore};
+use crate::recursion::LIMBS;
+
+#[derive(Debug)]
+pub enum Error {
+    NoLeaves,
+    /// Batches of less than two snarks never reach a root.
+    FanOut(usize),
+    /// `TreeConfig::k` has fewer degrees than the tree has layers.
+    MissingLayers {
+        num_layers: usize,
+        given: usize,
+    },
+    /// A layer's degree exceeds the SRS it would be downsized from.
+    Degree {
+        k: u32,
+        srs_k: u32,
+    },
+    KeyStore(keystore::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoLeaves => write!(f, "an aggregation tree needs at least one leaf"),
+            Error::FanOut(fan_out) => write!(f, "fan-out {fan_out} is less than 2"),
+            Error::MissingLayers { num_layers, given } => write!(
+                f,
+                "the tree has {num_layers} layers but k is given for {given}"
+            ),
+            Error::Degree { k, srs_k } => {
+                write!(f, "layer degree {k} exceeds the SRS degree {srs_k}")
+            }
+            Error::KeyStore(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<keystore::Error> for Error {
+    fn from(e: keystore::Error) -> Self {
+        Error::KeyStore(e)
+    }
+}
+
+/// Shape of an aggregation tree.
+#[derive(Clone, Debug)]
+pub struct TreeConfig {
+    /// Snarks aggregated by each node.
+    pub fan_out: usize,
+    /// Degree of each layer's aggregation circuits, from the layer aggregating the leaves to
+    /// the root. It has to fit `fan_out` snarks of the layer below, extra degrees are unused.
+    pub k: Vec<u32>,
+}
+
+impl TreeConfig {
+    /// Layers of aggregation snarks above `num_leaves` leaves, including the root.
+    pub fn num_layers(&self, num_leaves: usize) -> usize {
+        let mut num_layers = 1;
+        let mut num_nodes = num_leaves.div_ceil(self.fan_out);
+        while num_nodes > 1 {
+            num_nodes = num_nodes.div_ceil(self.fan_out);
+            num_layers += 1;
+        }
+        num_layers
+    }
+}
+
+/// Where the leaves of a tree are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    pub fan_out: usize,
+    /// Degree of each layer, from the layer aggregating the leaves to the root.
+    pub k: Vec<u32>,
+    /// Where each leaf's instances are among the root's, in the order of the leaves.
+    pub leaves: Vec<SnarkInstances>,
+}
+
+impl Manifest {
+    /// Index of the node aggregating `leaf` in `layer`, 0 being the layer above the leaves.
+    pub fn node(&self, leaf: usize, layer: usize) -> usize {
+        leaf / self.fan_out.pow(layer as u32 + 1)
+    }
+}
+
+pub struct AggregationTree {
+    pub root: Snark,
+    pub vk: VerifyingKey<G1Affine>,
+    pub manifest: Manifest,
+}
+
+/// Proving keys of a layer's nodes, generated once per distinct circuit: the nodes of a
+/// layer share one, except a last node aggregating fewer snarks or other leaf circuits.
+fn gen_layer_pks(
+    params: &ParamsKZG<Bn256>,
+    circuits: &[AggregationCircuit],
+    store: &KeyStore,
+) -> Result<(Vec<ProvingKey<G1Affine>>, Vec<usize>), keystore::Error> {
+    let mut shapes = Vec::new();
+    let mut pks = Vec::new();
+    let indices = circuits
+        .iter()
+        .map(|circuit| {
+            let shape = circuit.shape();
+            if let Some(index) = shapes.iter().position(|known| *known == shape) {
+                return Ok(index);
+            }
+            pks.push(gen_aggregation_pk(params, circuit, store)?);
+            shapes.push(shape);
+            Ok(pks.len() - 1)
+        })
+        .collect::<Result<Vec<_>, keystore::Error>>()?;
+    Ok((pks, indices))
+}
+
+/// Aggregates `leaves` into a tree shaped by `config`, proving the nodes of each layer in
+/// parallel on the rayon thread pool (bound it with `RAYON_NUM_THREADS`, each prover holds
+/// its own witness). `params` is the SRS of the largest degree, the leaves have to be proven
+/// over it (see `aggregation::gen_snark`), and it is downsized to each layer's degree.
+pub fn gen_aggregation_tree(
+    params: &ParamsKZG<Bn256>,
+    config: &TreeConfig,
+    leaves: Vec<Snark>,
+    store: &KeyStore,
+) -> Result<AggregationTree, Error> {
+    if leaves.is_empty() {
+        return Err(Error::NoLeaves);
+    }
+    if config.fan_out < 2 {
+        return Err(Error::FanOut(config.fan_out));
+    }
+    let num_layers = config.num_layers(leaves.len());
+    if config.k.len() < num_layers {
+        return Err(Error::MissingLayers {
+            num_layers,
+            given: config.k.len(),
+        });
+    }
+    let k = config.k[..num_layers].to_vec();
+    if let Some(&layer_k) = k.iter().find(|&&layer_k| layer_k > params.k()) {
+        return Err(Error::Degree {
+            k: layer_k,
+            srs_k: params.k(),
+        });
+    }
+
+    let mut snarks = leaves;
+    let mut manifest = None;
+    let mut vk = None;
+    for &layer_k in &k {
+        let params = downsize(params, layer_k);
+        let circuits = snarks
+            .par_chunks(config.fan_out)
+            .map(|batch| AggregationCircuit::new(&params, batch.to_vec()))
+            .collect::<Vec<_>>();
+
+        // the nodes forward the instances of the leaves in order, so their offsets in the
+        // root follow each other after the root's accumulator
+        manifest.get_or_insert_with(|| {
+            let mut offset = 4 * LIMBS;
+            let leaves = circuits
+                .iter()
+                .flat_map(AggregationCircuit::layout)
+                .map(|leaf| {
+                    let leaf = SnarkInstances {
+                        offset,
+                        ..leaf.clone()
+                    };
+                    offset += leaf.num_instance.iter().sum::<usize>();
+                    leaf
+                })
+                .collect();
+            Manifest {
+                fan_out: config.fan_out,
+                k: k.clone(),
+                leaves,
+            }
+        });
+
+        let (pks, indices) = gen_layer_pks(&params, &circuits, store)?;
+        snarks = circuits
+            .into_par_iter()
+            .zip(indices)
+            .map(|(circuit, index)| gen_aggregation_snark(&params, &pks[index], circuit))
+            .collect();
+        vk = pks.into_iter().next().map(|pk| pk.get_vk().clone());
+    }
+
+    Ok(AggregationTree {
+        root: snarks.pop().unwrap(),
+        vk: vk.unwrap(),
+        manifest: manifest.unwrap(),
+    })
+}
+
+/// Verifies the root of a tree against `vk`, and that its leaves had the `expected` instances
+/// per column, in the order of the leaves.
+pub fn verify_aggregation_tree(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    root: &Snark,
+    manifest: &Manifest,
+    expected: &[Vec<Vec<Fr>>],
+) -> bool {
+    let params = downsize(params, *manifest.k.last().unwrap());
+    verify_aggregation_snark(&params, vk, root, &manifest.leaves, expected)
+}
@@ -1,3 +1,4 @@
+use bench_alloc::{measure, CountingAllocator, Phase};
 use core::time::Duration;
 use criterion::*;
 use ff::Field;
@@ -17,6 +18,9 @@ targets = bench_recursive_snark_proof, bench_recursive_snark_verify
 
 criterion_main!(recursive_snark);
 
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
 fn bench_recursive_snark_proof(c: &mut Criterion) {
     let cases = vec![3, 10, 100];
 
@@ -27,12 +31,14 @@ fn bench_recursive_snark_proof(c: &mut Criterion) {
     let x2 = Field::random(&mut rng);
     let x3 = Field::random(&mut rng);
     // produce public parameters
-    let pp = public_params();
+    let (pp, memory) = measure("Nova-Poseidon/Setup", public_params);
+    println!("{memory}");
 
     for num_steps in cases {
         let mut group = c.benchmark_group(format!("Nova-Poseidon-num-steps-{}", num_steps));
         group.sample_size(10);
 
+        let phase = Phase::start(format!("Nova-Poseidon-num-steps-{}/Prove", num_steps));
         group.bench_function("Prove", |b| {
             b.iter(|| {
                 let initial_state = vec![x0, x1, x2, x3];
@@ -45,6 +51,7 @@ fn bench_recursive_snark_proof(c: &mut Criterion) {
                 NovaChainHashProof::prove_recursively(&pp, &circuits, z0.clone()).unwrap();
             })
         });
+        println!("{}", phase.finish());
         group.finish();
     }
 }
@@ -59,7 +66,8 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
     let x2 = Field::random(&mut rng);
     let x3 = Field::random(&mut rng);
     // produce public parameters
-    let pp = public_params();
+    let (pp, memory) = measure("Nova-Poseidon/Setup", public_params);
+    println!("{memory}");
 
     println!(
         "Number of constraints per step (primary circuit): {}",
@@ -103,6 +111,7 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
             num_steps
         );
     
+        let phase = Phase::start(format!("Nova-Poseidon-num-steps-{}/Verify", num_steps));
         group.bench_function("Verify", |b| {
             b.iter(|| {
                 let res = recursive_snark.verify(&pp, num_steps, z0.clone(), &zi);
@@ -113,6 +122,7 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
                 assert!(res.unwrap());
             })
         });
+        println!("{}", phase.finish());
         group.finish();
     }
 }
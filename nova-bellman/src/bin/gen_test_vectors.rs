@@ -0,0 +1,14 @@
+use std::env;
+
+use nova_bellman::test_vectors::{generate_test_vectors, write_test_vectors, TEST_VECTORS_PATH};
+
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| TEST_VECTORS_PATH.to_string());
+
+    let vectors = generate_test_vectors();
+    write_test_vectors(&path, &vectors).unwrap();
+
+    println!("Wrote {} test vectors to {}", vectors.len(), path);
+}
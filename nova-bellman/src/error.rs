@@ -6,4 +6,7 @@ use nova::errors::NovaError;
 pub enum Error {
     Nova(NovaError),
     Synthesis(SynthesisError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    InvalidFieldElement(String),
 }
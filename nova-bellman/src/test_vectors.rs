@@ -0,0 +1,127 @@
+use std::{fs, path::Path};
+
+use ff::{Field, PrimeField};
+use neptune::poseidon::PoseidonConstants;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculation::calculate_chain_hash,
+    error::Error,
+    public_params::{A1, S1},
+    TEST_SEED,
+};
+
+pub const TEST_VECTORS_PATH: &str = "test_vectors/poseidon_chain_hash.json";
+
+pub const TEST_VECTOR_ITERATIONS: [usize; 4] = [1, 3, 10, 100];
+
+/// Known-answer vector for `calculate_chain_hash`.
+///
+/// Field elements are stored as big-endian `0x`-prefixed hex strings so other
+/// Poseidon implementations can consume the file without knowing the pasta repr.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainHashTestVector {
+    pub field: String,
+    pub modulus: String,
+    pub width: usize,
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    pub iterations: usize,
+    pub input: Vec<String>,
+    pub output: Vec<String>,
+}
+
+impl ChainHashTestVector {
+    pub fn new(input: Vec<S1>, iterations: usize) -> Self {
+        let constants = PoseidonConstants::<S1, A1>::new();
+        let output = calculate_chain_hash(input.clone(), iterations);
+
+        ChainHashTestVector {
+            field: "pallas::Scalar".to_string(),
+            modulus: S1::MODULUS.to_string(),
+            width: constants.width(),
+            full_rounds: constants.full_rounds,
+            partial_rounds: constants.partial_rounds,
+            iterations,
+            input: input.iter().map(encode_field_element).collect(),
+            output: output.iter().map(encode_field_element).collect(),
+        }
+    }
+
+    /// Recomputes the chain hash and checks it, along with the Poseidon
+    /// parameters, against the stored vector.
+    pub fn check(&self) -> Result<bool, Error> {
+        let input = self
+            .input
+            .iter()
+            .map(|s| decode_field_element(s))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(*self == Self::new(input, self.iterations))
+    }
+}
+
+pub fn generate_test_vectors() -> Vec<ChainHashTestVector> {
+    let mut rng = XorShiftRng::from_seed(TEST_SEED);
+
+    let inputs = vec![
+        vec![S1::ZERO; 4],
+        (0..4u64).map(S1::from).collect::<Vec<_>>(),
+        (0..4).map(|_| S1::random(&mut rng)).collect::<Vec<_>>(),
+    ];
+
+    inputs
+        .iter()
+        .flat_map(|input| {
+            TEST_VECTOR_ITERATIONS
+                .iter()
+                .map(|&iterations| ChainHashTestVector::new(input.clone(), iterations))
+        })
+        .collect()
+}
+
+pub fn write_test_vectors<P: AsRef<Path>>(
+    path: P,
+    vectors: &[ChainHashTestVector],
+) -> Result<(), Error> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent).map_err(Error::Io)?;
+    }
+    let json = serde_json::to_string_pretty(vectors).map_err(Error::Json)?;
+    fs::write(path, json).map_err(Error::Io)
+}
+
+pub fn read_test_vectors<P: AsRef<Path>>(path: P) -> Result<Vec<ChainHashTestVector>, Error> {
+    let json = fs::read_to_string(path).map_err(Error::Io)?;
+    serde_json::from_str(&json).map_err(Error::Json)
+}
+
+pub fn encode_field_element(value: &S1) -> String {
+    let hex = value
+        .to_repr()
+        .as_ref()
+        .iter()
+        .rev()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    format!("0x{hex}")
+}
+
+pub fn decode_field_element(value: &str) -> Result<S1, Error> {
+    let invalid = || Error::InvalidFieldElement(value.to_string());
+
+    let hex = value.strip_prefix("0x").ok_or_else(invalid)?;
+    let mut repr = <S1 as PrimeField>::Repr::default();
+    if hex.len() != 2 * repr.as_ref().len() {
+        return Err(invalid());
+    }
+
+    for (byte, chunk) in repr.as_mut().iter_mut().rev().zip(hex.as_bytes().chunks(2)) {
+        let chunk = std::str::from_utf8(chunk).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(chunk, 16).map_err(|_| invalid())?;
+    }
+
+    Option::from(S1::from_repr(repr)).ok_or_else(invalid)
+}
@@ -3,8 +3,14 @@ mod test {
     use crate::calculation::calculate_chain_hash;
     use crate::poseidon_chain_hash_proof::NovaChainHashProof;
     use crate::public_params::public_params;
+    use crate::test_vectors::{
+        decode_field_element, encode_field_element, generate_test_vectors, read_test_vectors,
+        write_test_vectors, ChainHashTestVector, TEST_VECTORS_PATH,
+    };
     use crate::{PoseidonHashChainCircuit, TEST_SEED};
 
+    use std::path::Path;
+
     use ff::Field;
     use rand::SeedableRng;
     use rand_xorshift::XorShiftRng;
@@ -57,4 +63,70 @@ mod test {
         }
         assert!(res.unwrap());
     }
+
+    #[test]
+    fn test_field_element_encoding_roundtrip() {
+        let mut rng = XorShiftRng::from_seed(TEST_SEED);
+
+        for _ in 0..10 {
+            let value = Field::random(&mut rng);
+            let encoded = encode_field_element(&value);
+            assert_eq!(decode_field_element(&encoded).unwrap(), value);
+        }
+
+        assert!(decode_field_element("0x01").is_err());
+        assert!(decode_field_element(&"f".repeat(66)).is_err());
+    }
+
+    #[test]
+    fn test_vectors_roundtrip() {
+        let vectors = generate_test_vectors();
+        let path = std::env::temp_dir().join("nova_bellman_poseidon_chain_hash.json");
+
+        write_test_vectors(&path, &vectors).unwrap();
+        let loaded = read_test_vectors(&path).unwrap();
+
+        assert_eq!(vectors, loaded);
+        for vector in loaded {
+            assert!(vector.check().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_vectors_tampered_output() {
+        let mut vector = ChainHashTestVector::new(vec![Field::ONE; 4], 3);
+        vector.output[0] = encode_field_element(&Field::ONE);
+
+        assert!(!vector.check().unwrap());
+    }
+
+    #[test]
+    fn test_vectors_known_answers() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(TEST_VECTORS_PATH);
+        let vectors = read_test_vectors(&path).unwrap_or_else(|e| {
+            panic!(
+                "{}: {:?}, run `cargo run --bin gen_test_vectors` to create it",
+                path.display(),
+                e
+            )
+        });
+
+        // the committed file covers every input and iteration count we generate
+        let cases = |vectors: &[ChainHashTestVector]| {
+            vectors
+                .iter()
+                .map(|vector| (vector.input.clone(), vector.iterations))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(cases(&vectors), cases(&generate_test_vectors()));
+
+        for vector in vectors {
+            assert!(
+                vector.check().unwrap(),
+                "chain hash mismatch for {} iterations of {:?}",
+                vector.iterations,
+                vector.input
+            );
+        }
+    }
 }
@@ -1,16 +1,26 @@
+use bench_alloc::{measure, Phase};
 use core::time::Duration;
 use criterion::*;
+use flate2::{write::ZlibEncoder, Compression};
+use nova_bench::{
+    artifacts::CircomArtifacts,
+    encoded_len, fold_witnesses,
+    poseidon::start_public_input,
+    private_inputs::PrivateInputs,
+    verify_chain,
+    witness::{Witnesses, WTNS_ENV},
+    NovaCompressedSNARK,
+};
 use nova_scotia::{
     circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F1, G2,
 };
 use nova_snark::traits::Group;
-use std::{collections::HashMap, env::current_dir};
-use flate2::{write::ZlibEncoder, Compression};
+use std::env;
 
 criterion_group! {
     name = recursive_snark;
     config = Criterion::default().warm_up_time(Duration::from_millis(3000));
-    targets = bench_recursive_snark_proove, bench_recursive_snark_verify
+    targets = bench_recursive_snark_proove, bench_recursive_snark_proove_precomputed_witness, bench_recursive_snark_verify, bench_compressed_snark
 }
 
 criterion_main!(recursive_snark);
@@ -18,38 +28,29 @@ criterion_main!(recursive_snark);
 fn bench_recursive_snark_proove(c: &mut Criterion) {
     let cases = vec![3, 10, 100];
 
-    let root = current_dir().unwrap();
-
-    // has to be generated by circom before running the code
-    let circuit_file = root.join("./examples/poseidon/circom/poseidon_test_nova.r1cs");
-    let r1cs = load_r1cs(&FileLocation::PathBuf(circuit_file));
     // has to be generated by circom before running the code
-    let witness_generator_wasm =
-        root.join("./examples/poseidon/circom/poseidon_test_nova_js/poseidon_test_nova.wasm");
+    let artifacts = CircomArtifacts::builder().build().unwrap();
+    let r1cs = load_r1cs(&FileLocation::PathBuf(artifacts.r1cs.clone()));
+    let witness_generator_wasm = artifacts.wasm.clone();
 
     // Creating public parameters
-    let pp = create_public_params(r1cs.clone());
+    let (pp, memory) = measure("Nova-Circom-Poseidon/Setup", || {
+        create_public_params(r1cs.clone())
+    });
+    println!("{memory}");
 
     for k in cases {
         let iteration_count = k;
 
-        let step_in_vector = vec![0, 1, 2, 3];
-
         // We don't have any private inputs, so corresponding `HashMap`s are empty, thoough we still need to create and pass them
-        let mut private_inputs = Vec::new();
-        for _ in 0..iteration_count {
-            let private_input = HashMap::new();
-            private_inputs.push(private_input);
-        }
+        let private_inputs = PrivateInputs::empty(iteration_count).into_inner();
 
-        let start_public_input = step_in_vector
-            .into_iter()
-            .map(|x| F1::from(x))
-            .collect::<Vec<_>>();
+        let start_public_input = start_public_input::<F1>();
 
         let mut group = c.benchmark_group(format!("Nova-Circom-Poseidon-num-steps-{}", k));
         group.sample_size(10);
 
+        let phase = Phase::start(format!("Nova-Circom-Poseidon-num-steps-{}/Prove", k));
         group.bench_function("Prove", |b| {
             b.iter(|| {
                 // create a recursive SNARK
@@ -63,23 +64,56 @@ fn bench_recursive_snark_proove(c: &mut Criterion) {
                 .unwrap();
             })
         });
+        println!("{}", phase.finish());
         group.finish();
     }
 }
 
+fn bench_recursive_snark_proove_precomputed_witness(c: &mut Criterion) {
+    // witnesses generated offline for `step_in = [0, 1, 2, 3]`, so only folding is measured
+    let Some(path) = env::var_os(WTNS_ENV) else {
+        println!("{WTNS_ENV} not set, skipping the precomputed witness bench");
+        return;
+    };
+    let witnesses = Witnesses::from_files(&[path]).unwrap();
+    let num_steps = witnesses.len();
+
+    let artifacts = CircomArtifacts::builder().build().unwrap();
+    let r1cs = load_r1cs(&FileLocation::PathBuf(artifacts.r1cs.clone()));
+    let pp = create_public_params(r1cs.clone());
+
+    let start_public_input = start_public_input::<F1>();
+
+    let mut group = c.benchmark_group(format!("Nova-Circom-Poseidon-num-steps-{}", num_steps));
+    group.sample_size(10);
+
+    let phase = Phase::start(format!(
+        "Nova-Circom-Poseidon-num-steps-{}/Prove-Precomputed-Witness",
+        num_steps
+    ));
+    group.bench_function("Prove-Precomputed-Witness", |b| {
+        b.iter(|| {
+            fold_witnesses(
+                &artifacts,
+                r1cs.clone(),
+                &pp,
+                start_public_input.clone(),
+                witnesses.clone(),
+            )
+            .unwrap();
+        })
+    });
+    println!("{}", phase.finish());
+    group.finish();
+}
+
 fn bench_recursive_snark_verify(c: &mut Criterion) {
     let cases = vec![3, 10, 100];
 
-    let root = current_dir().unwrap();
-
     // has to be generated by circom before running the code
-    let circuit_file = root.join("./examples/poseidon/circom/poseidon_test_nova.r1cs");
-    let r1cs = load_r1cs(&FileLocation::PathBuf(circuit_file));
-    // has to be generated by circom before running the code
-    let witness_generator_wasm =
-        root.join("./examples/poseidon/circom/poseidon_test_nova_js/poseidon_test_nova.wasm");
-
-    let step_in_vector = vec![0, 1, 2, 3];
+    let artifacts = CircomArtifacts::builder().build().unwrap();
+    let r1cs = load_r1cs(&FileLocation::PathBuf(artifacts.r1cs.clone()));
+    let witness_generator_wasm = artifacts.wasm.clone();
 
     // Creating public parameters
     let pp = create_public_params(r1cs.clone());
@@ -106,17 +140,9 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
         let iteration_count = k;
 
         // We don't have any private inputs, so corresponding `HashMap`s are empty, thoough we still need to create and pass them
-        let mut private_inputs = Vec::new();
-        for _ in 0..iteration_count {
-            let private_input = HashMap::new();
-            private_inputs.push(private_input);
-        }
-
-        let start_public_input = step_in_vector
-            .clone()
-            .into_iter()
-            .map(|x| F1::from(x))
-            .collect::<Vec<_>>();
+        let private_inputs = PrivateInputs::empty(iteration_count).into_inner();
+
+        let start_public_input = start_public_input::<F1>();
 
         // create a recursive SNARK
         let recursive_snark = create_recursive_circuit(
@@ -130,6 +156,15 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
 
         let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
 
+        // the folded output has to match the native Poseidon chain
+        verify_chain(
+            &pp,
+            &recursive_snark,
+            iteration_count,
+            start_public_input.clone(),
+        )
+        .unwrap();
+
         let mut group = c.benchmark_group(format!("Nova-Circom-Poseidon-num-steps-{}", k));
         group.sample_size(10);
 
@@ -142,6 +177,7 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
             k
         );
 
+        let phase = Phase::start(format!("Nova-Circom-Poseidon-num-steps-{}/Verify", k));
         group.bench_function("Verify", |b| {
             b.iter(|| {
                 let res = recursive_snark.verify(
@@ -153,6 +189,92 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
                 assert!(res.is_ok());
             })
         });
+        println!("{}", phase.finish());
+        group.finish();
+    }
+}
+
+fn bench_compressed_snark(c: &mut Criterion) {
+    let cases = vec![3, 10, 100];
+
+    // has to be generated by circom before running the code
+    let artifacts = CircomArtifacts::builder().build().unwrap();
+    let r1cs = load_r1cs(&FileLocation::PathBuf(artifacts.r1cs.clone()));
+    let witness_generator_wasm = artifacts.wasm.clone();
+
+    // Creating public parameters
+    let pp = create_public_params(r1cs.clone());
+
+    // pk - public key
+    // vk - verifying key
+    // The keys only depend on the public parameters, so they are shared by all cases
+    let (pk, vk) = NovaCompressedSNARK::setup(&pp).unwrap();
+
+    let mut group = c.benchmark_group("Nova-Circom-Poseidon-compressed");
+    group.sample_size(10);
+    let phase = Phase::start("Nova-Circom-Poseidon-compressed/Setup");
+    group.bench_function("Setup", |b| {
+        b.iter(|| {
+            NovaCompressedSNARK::setup(&pp).unwrap();
+        })
+    });
+    println!("{}", phase.finish());
+    group.finish();
+
+    for k in cases {
+        let iteration_count = k;
+
+        // We don't have any private inputs, so corresponding `HashMap`s are empty, thoough we still need to create and pass them
+        let private_inputs = PrivateInputs::empty(iteration_count).into_inner();
+
+        let start_public_input = start_public_input::<F1>();
+
+        // create a recursive SNARK
+        let recursive_snark = create_recursive_circuit(
+            FileLocation::PathBuf(witness_generator_wasm.clone()),
+            r1cs.clone(),
+            private_inputs.clone(),
+            start_public_input.clone(),
+            &pp,
+        )
+        .unwrap();
+
+        let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+
+        let compressed_snark = NovaCompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+        println!(
+            "Nova Circom CompressedSNARK::len {:?} bytes for case {:?}",
+            encoded_len(&compressed_snark),
+            k
+        );
+
+        let mut group = c.benchmark_group(format!("Nova-Circom-Poseidon-num-steps-{}", k));
+        group.sample_size(10);
+
+        let phase = Phase::start(format!("Nova-Circom-Poseidon-num-steps-{}/Compress", k));
+        group.bench_function("Compress", |b| {
+            b.iter(|| {
+                NovaCompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+            })
+        });
+        println!("{}", phase.finish());
+
+        let phase = Phase::start(format!(
+            "Nova-Circom-Poseidon-num-steps-{}/Verify-Compressed",
+            k
+        ));
+        group.bench_function("Verify-Compressed", |b| {
+            b.iter(|| {
+                let res = compressed_snark.verify(
+                    &vk,
+                    iteration_count,
+                    start_public_input.clone(),
+                    z0_secondary.clone(),
+                );
+                assert!(res.is_ok());
+            })
+        });
+        println!("{}", phase.finish());
         group.finish();
     }
 }
@@ -1,6 +1,6 @@
 use ark_std::{end_timer, start_timer};
 
-use std::{collections::HashMap, env, env::current_dir, time::Instant};
+use std::{collections::HashMap, env, process, time::Instant};
 
 use nova_scotia::{
     circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F1,
@@ -8,35 +8,23 @@ use nova_scotia::{
 };
 // Ignore create_recursive_circuit
 
+use nova_bench::{artifacts::CircomArtifacts, check_chain_output};
 use nova_snark::{traits::Group, CompressedSNARK};
 
-extern crate wee_alloc;
-
-// NOTE: Supposedly this helps against segfaults, but seems intermittent
-// Consider trying jemallocator (?)
-// Alternatively, just run larger benchmarks on a server (with C++ version)
-//
-// Use `wee_alloc` as the global allocator.
-#[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+// NOTE: `wee_alloc` is installed as the global allocator by `nova_bench`,
+// which this example links for `CircomArtifacts`.
 
 /// Creates a recursive SNARK, that proves the recursive (cyclic) calculation, of 4 values using Poseidon hashing function
 ///
 /// Panic
 ///
-/// Code panics in case needed `poseidon_test_nova.r1cs` and/or `poseidon_test_nova.wasm` do no exist at specified paths
-fn recursive_hashing(depth: usize) {
+/// Code panics in case the witness generation or one of the proofs fails
+fn recursive_hashing(artifacts: &CircomArtifacts, depth: usize) {
     println! {"Using recursive depth: {:?} times depth_per_fold in circuit (default 10 or 100, check yourself! :D)", depth};
 
     let iteration_count = depth;
-    let root = current_dir().unwrap();
-
-    // has to be generated by circom before running the code
-    let circuit_file = root.join("./examples/poseidon/circom/poseidon_test_nova.r1cs");
-    let r1cs = load_r1cs(&FileLocation::PathBuf(circuit_file));
-    // has to be generated by circom before running the code
-    let witness_generator_wasm =
-        root.join("./examples/poseidon/circom/poseidon_test_nova_js/poseidon_test_nova.wasm");
+    let r1cs = load_r1cs(&FileLocation::PathBuf(artifacts.r1cs.clone()));
+    let witness_generator_wasm = artifacts.wasm.clone();
 
     let step_in_vector = vec![0, 1, 2, 3];
 
@@ -101,6 +89,10 @@ fn recursive_hashing(depth: usize) {
 
     end_timer!(timer_verify_snark);
 
+    // compare the folded output with the native Poseidon chain
+    let (zn_primary, _) = res.unwrap();
+    check_chain_output(&start_public_input, iteration_count, &zn_primary).unwrap();
+
     // produce a compressed SNARK
     let timer_gen_compressed_snark =
         start_timer!(|| "Generate a CompressedSNARK using Spartan with IPA-PC");
@@ -131,6 +123,16 @@ fn main() {
     let k = 10;
     //let poseidon_block: u64 = args[2].parse().unwrap();
 
+    // has to be generated by circom before running the code,
+    // set NOVA_BENCH_R1CS / NOVA_BENCH_WASM to use another location
+    let artifacts = match CircomArtifacts::builder().build() {
+        Ok(artifacts) => artifacts,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+
     // NOTE: Toggle here
-    recursive_hashing(k);
+    recursive_hashing(&artifacts, k);
 }
@@ -0,0 +1,170 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use num_bigint::BigUint;
+use num_traits::Num;
+
+use crate::error::Error;
+
+/// Environment variable overriding the location of the circuit `.r1cs` file.
+pub const R1CS_ENV: &str = "NOVA_BENCH_R1CS";
+/// Environment variable overriding the location of the witness generator `.wasm` file.
+pub const WASM_ENV: &str = "NOVA_BENCH_WASM";
+
+pub const DEFAULT_R1CS: &str = "examples/poseidon/circom/poseidon_test_nova.r1cs";
+pub const DEFAULT_WASM: &str =
+    "examples/poseidon/circom/poseidon_test_nova_js/poseidon_test_nova.wasm";
+
+/// Scalar field of the primary curve (pallas), which circom calls `vesta`.
+pub const PRIMARY_PRIME: &str = "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001";
+
+/// Validated locations of a compiled circom circuit.
+#[derive(Clone, Debug)]
+pub struct CircomArtifacts {
+    pub r1cs: PathBuf,
+    pub wasm: PathBuf,
+    pub header: R1csHeader,
+}
+
+impl CircomArtifacts {
+    pub fn builder() -> CircomArtifactsBuilder {
+        CircomArtifactsBuilder::default()
+    }
+
+    pub fn new<P: Into<PathBuf>, Q: Into<PathBuf>>(r1cs: P, wasm: Q) -> Result<Self, Error> {
+        Self::builder().r1cs(r1cs).wasm(wasm).build()
+    }
+}
+
+/// Builder for `CircomArtifacts`.
+///
+/// Unset locations fall back to `NOVA_BENCH_R1CS` / `NOVA_BENCH_WASM`, and then
+/// to the Poseidon example circuit under the crate directory.
+#[derive(Clone, Debug, Default)]
+pub struct CircomArtifactsBuilder {
+    r1cs: Option<PathBuf>,
+    wasm: Option<PathBuf>,
+}
+
+impl CircomArtifactsBuilder {
+    pub fn r1cs<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.r1cs = Some(path.into());
+        self
+    }
+
+    pub fn wasm<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.wasm = Some(path.into());
+        self
+    }
+
+    /// Resolves both locations, checks that the files exist and that the r1cs
+    /// was compiled for the primary curve's scalar field.
+    pub fn build(self) -> Result<CircomArtifacts, Error> {
+        let r1cs = resolve(self.r1cs, R1CS_ENV, DEFAULT_R1CS);
+        let wasm = resolve(self.wasm, WASM_ENV, DEFAULT_WASM);
+
+        for path in [&r1cs, &wasm] {
+            if !path.is_file() {
+                return Err(Error::MissingArtifact(path.clone()));
+            }
+        }
+
+        let header = R1csHeader::read(&r1cs)?;
+        let expected = BigUint::from_str_radix(PRIMARY_PRIME, 16).unwrap();
+        if header.prime != expected {
+            return Err(Error::WrongPrime {
+                path: r1cs,
+                expected,
+                found: header.prime,
+            });
+        }
+
+        Ok(CircomArtifacts { r1cs, wasm, header })
+    }
+}
+
+fn resolve(path: Option<PathBuf>, var: &str, default: &str) -> PathBuf {
+    path.or_else(|| env::var_os(var).map(PathBuf::from))
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(default))
+}
+
+/// Header section of a circom `.r1cs` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1csHeader {
+    pub prime: BigUint,
+    pub num_wires: u32,
+    pub num_pub_out: u32,
+    pub num_pub_in: u32,
+    pub num_prv_in: u32,
+    pub num_labels: u64,
+    pub num_constraints: u32,
+}
+
+impl R1csHeader {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let invalid = |reason: String| Error::InvalidArtifact {
+            path: path.to_path_buf(),
+            reason,
+        };
+
+        let file = File::open(path).map_err(|e| invalid(e.to_string()))?;
+        Self::read_from(&mut BufReader::new(file)).map_err(|e| invalid(e.to_string()))
+    }
+
+    fn read_from<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"r1cs" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an r1cs file",
+            ));
+        }
+        let _version = read_u32(reader)?;
+        let num_sections = read_u32(reader)?;
+
+        for _ in 0..num_sections {
+            let section_type = read_u32(reader)?;
+            let section_size = read_u64(reader)?;
+            if section_type != 1 {
+                reader.seek(SeekFrom::Current(section_size as i64))?;
+                continue;
+            }
+
+            let field_size = read_u32(reader)? as usize;
+            let mut prime = vec![0u8; field_size];
+            reader.read_exact(&mut prime)?;
+
+            return Ok(R1csHeader {
+                prime: BigUint::from_bytes_le(&prime),
+                num_wires: read_u32(reader)?,
+                num_pub_out: read_u32(reader)?,
+                num_pub_in: read_u32(reader)?,
+                num_prv_in: read_u32(reader)?,
+                num_labels: read_u64(reader)?,
+                num_constraints: read_u32(reader)?,
+            });
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "r1cs header section not found",
+        ))
+    }
+}
+
+pub(crate) fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
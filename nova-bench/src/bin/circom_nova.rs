@@ -0,0 +1,216 @@
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use ff::PrimeField;
+use nova_bench::{
+    artifacts::CircomArtifacts, compress, encoded_len, error, fold, fold_witnesses,
+    private_inputs::PrivateInputs, public_params, witness::Witnesses,
+};
+use nova_scotia::{F1, G2};
+use nova_snark::traits::Group;
+use serde::Serialize;
+
+#[derive(Parser)]
+#[command(about = "Fold any circom circuit with Nova and report constraints, timings and sizes")]
+struct Cli {
+    /// Circuit compiled with `--r1cs --prime vesta`
+    #[arg(long)]
+    r1cs: PathBuf,
+    /// Witness calculator compiled with `--wasm`
+    #[arg(long)]
+    wasm: PathBuf,
+    /// Initial `step_in`, comma separated decimal field elements
+    #[arg(long, value_delimiter = ',', required = true)]
+    step_in: Vec<String>,
+    /// Per-step private inputs, a JSON array or JSONL file with one object per step
+    #[arg(long)]
+    private_inputs: Option<PathBuf>,
+    /// Precomputed `.wtns` witnesses in step order, each file holding one step or
+    /// several back to back, used instead of the WASM witness calculator
+    #[arg(long, value_delimiter = ',', conflicts_with = "private_inputs")]
+    witness: Vec<PathBuf>,
+    /// Number of folding steps, defaults to the number of steps in `private-inputs`
+    /// or `witness`
+    #[arg(long)]
+    steps: Option<usize>,
+    /// Also compress the folded proof with Spartan
+    #[arg(long)]
+    compress: bool,
+    /// Write the JSON report here instead of stdout
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    r1cs: PathBuf,
+    wasm: PathBuf,
+    num_steps: usize,
+    constraints_primary: usize,
+    constraints_secondary: usize,
+    variables_primary: usize,
+    variables_secondary: usize,
+    public_params_ms: f64,
+    prove_ms: f64,
+    verify_ms: f64,
+    /// bincode + zlib, as in the benches
+    recursive_snark_bytes: usize,
+    compressed: Option<CompressedReport>,
+    zn_primary: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct CompressedReport {
+    setup_ms: f64,
+    prove_ms: f64,
+    verify_ms: f64,
+    bytes: usize,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let artifacts = CircomArtifacts::new(&cli.r1cs, &cli.wasm)?;
+    let header = &artifacts.header;
+
+    // Nova-Scotia folds `step_in` into `step_out`, both public and of the same size
+    if header.num_pub_in != header.num_pub_out {
+        return Err(format!(
+            "{} has {} public inputs and {} outputs, Nova needs `step_in` and `step_out` of the same size",
+            cli.r1cs.display(),
+            header.num_pub_in,
+            header.num_pub_out
+        )
+        .into());
+    }
+    if cli.step_in.len() != header.num_pub_in as usize {
+        return Err(format!(
+            "{} values given for step_in, the circuit declares {}",
+            cli.step_in.len(),
+            header.num_pub_in
+        )
+        .into());
+    }
+    let start_public_input = cli
+        .step_in
+        .iter()
+        .map(|value| {
+            F1::from_str_vartime(value.trim())
+                .ok_or_else(|| format!("invalid step_in value: {value}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let witnesses = if cli.witness.is_empty() {
+        None
+    } else {
+        let witnesses = Witnesses::from_files(&cli.witness)?;
+        match cli.steps {
+            Some(steps) if steps != witnesses.len() => {
+                return Err(format!(
+                    "--steps {steps} does not match the {} precomputed witnesses",
+                    witnesses.len()
+                )
+                .into())
+            }
+            _ => Some(witnesses),
+        }
+    };
+
+    let private_inputs = match (&cli.private_inputs, cli.steps) {
+        (Some(path), steps) => {
+            let private_inputs = PrivateInputs::from_file(path)?;
+            match steps {
+                Some(steps) if steps != private_inputs.len() => {
+                    return Err(format!(
+                        "--steps {steps} does not match the {} steps in {}",
+                        private_inputs.len(),
+                        path.display()
+                    )
+                    .into())
+                }
+                _ => private_inputs,
+            }
+        }
+        (None, Some(steps)) => PrivateInputs::empty(steps),
+        (None, None) => match &witnesses {
+            Some(witnesses) => PrivateInputs::empty(witnesses.len()),
+            None => return Err("one of --steps, --private-inputs or --witness is required".into()),
+        },
+    };
+    let num_steps = private_inputs.len();
+    if num_steps == 0 {
+        return Err("at least one step is required".into());
+    }
+
+    let start = Instant::now();
+    let (r1cs, pp) = public_params(&artifacts);
+    let public_params_time = start.elapsed();
+
+    let start = Instant::now();
+    let recursive_snark = match witnesses {
+        Some(witnesses) => {
+            fold_witnesses(&artifacts, r1cs, &pp, start_public_input.clone(), witnesses)?
+        }
+        None => fold(
+            &artifacts,
+            r1cs,
+            &pp,
+            start_public_input.clone(),
+            private_inputs,
+        )?,
+    };
+    let prove_time = start.elapsed();
+
+    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+
+    let start = Instant::now();
+    let (zn_primary, _) = recursive_snark
+        .verify(&pp, num_steps, start_public_input.clone(), z0_secondary)
+        .map_err(error::Error::Nova)?;
+    let verify_time = start.elapsed();
+
+    let compressed = if cli.compress {
+        let proof = compress(&pp, &recursive_snark, num_steps, start_public_input)?;
+        Some(CompressedReport {
+            setup_ms: millis(proof.setup_time),
+            prove_ms: millis(proof.prove_time),
+            verify_ms: millis(proof.verify_time),
+            bytes: proof.size,
+        })
+    } else {
+        None
+    };
+
+    let report = Report {
+        r1cs: cli.r1cs,
+        wasm: cli.wasm,
+        num_steps,
+        constraints_primary: pp.num_constraints().0,
+        constraints_secondary: pp.num_constraints().1,
+        variables_primary: pp.num_variables().0,
+        variables_secondary: pp.num_variables().1,
+        public_params_ms: millis(public_params_time),
+        prove_ms: millis(prove_time),
+        verify_ms: millis(verify_time),
+        recursive_snark_bytes: encoded_len(&recursive_snark),
+        compressed,
+        zn_primary: zn_primary.iter().map(|z| format!("{z:?}")).collect(),
+    };
+
+    let report = serde_json::to_string_pretty(&report)?;
+    match cli.report {
+        Some(path) => fs::write(path, report)?,
+        None => println!("{report}"),
+    }
+
+    Ok(())
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1e3
+}
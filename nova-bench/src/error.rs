@@ -0,0 +1,79 @@
+use std::{fmt, io, path::PathBuf};
+
+use nova_scotia::F1;
+use nova_snark::errors::NovaError;
+use num_bigint::BigUint;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A circom artifact does not exist at the configured location.
+    MissingArtifact(PathBuf),
+    /// An artifact exists but could not be read or parsed.
+    InvalidArtifact { path: PathBuf, reason: String },
+    /// The r1cs was compiled for a different prime than the primary curve's scalar field.
+    WrongPrime {
+        path: PathBuf,
+        expected: BigUint,
+        found: BigUint,
+    },
+    /// The private inputs of a step do not match the circuit's declared inputs.
+    InvalidPrivateInputs { step: usize, reason: String },
+    /// The WASM witness calculator failed while folding.
+    WitnessGeneration(io::Error),
+    /// A precomputed witness does not have one value per r1cs wire.
+    WitnessLengthMismatch {
+        step: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A precomputed witness does not fit the circuit or the previous step.
+    InvalidWitness { step: usize, reason: String },
+    /// Nova rejected a setup, proving or verification step.
+    Nova(NovaError),
+    /// The folded proof verifies, but its final `z_i` is not the native Poseidon chain output.
+    ChainOutputMismatch { expected: Vec<F1>, actual: Vec<F1> },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingArtifact(path) => {
+                write!(f, "circom artifact not found: {}", path.display())
+            }
+            Error::InvalidArtifact { path, reason } => {
+                write!(f, "invalid circom artifact {}: {reason}", path.display())
+            }
+            Error::WrongPrime {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} was compiled for prime {found}, expected {expected} (compile with `--prime vesta`)",
+                path.display()
+            ),
+            Error::InvalidPrivateInputs { step, reason } => {
+                write!(f, "invalid private inputs for step {step}: {reason}")
+            }
+            Error::WitnessGeneration(e) => write!(f, "witness generation failed: {e}"),
+            Error::WitnessLengthMismatch {
+                step,
+                expected,
+                found,
+            } => write!(
+                f,
+                "witness for step {step} has {found} values, the r1cs has {expected} wires"
+            ),
+            Error::InvalidWitness { step, reason } => {
+                write!(f, "invalid witness for step {step}: {reason}")
+            }
+            Error::Nova(e) => write!(f, "nova error: {e:?}"),
+            Error::ChainOutputMismatch { expected, actual } => write!(
+                f,
+                "folded output {actual:?} does not match the native Poseidon chain {expected:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,93 +1,341 @@
+pub mod artifacts;
+pub mod error;
+pub mod poseidon;
+pub mod private_inputs;
+pub mod tests;
+pub mod witness;
+
 use ark_std::{end_timer, start_timer};
 
-use std::{collections::HashMap, env, env::current_dir, time::Instant};
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, Instant},
+};
 
+use flate2::{write::ZlibEncoder, Compression};
 use nova_scotia::{
-    circom::{circuit::CircomCircuit, reader::load_r1cs},
+    circom::{
+        circuit::{CircomCircuit, R1CS},
+        reader::load_r1cs,
+    },
     create_public_params, create_recursive_circuit, FileLocation, F1, G1, G2, S1, S2,
 };
 // Ignore create_recursive_circuit
 
 use nova_snark::{
     traits::{circuit::TrivialTestCircuit, Group},
-    CompressedSNARK, RecursiveSNARK,
+    CompressedSNARK, PublicParams, RecursiveSNARK, VerifierKey,
+};
+use serde::Serialize;
+
+use crate::{
+    artifacts::CircomArtifacts,
+    error::Error,
+    poseidon::{expected_chain_output, start_public_input},
+    private_inputs::{DeclaredInputs, PrivateInputs},
+    witness::Witnesses,
 };
 
 extern crate wee_alloc;
 
+use bench_alloc::CountingAllocator;
+
 // NOTE: Supposedly this helps against segfaults, but seems intermittent
 // Consider trying jemallocator (?)
 // Alternatively, just run larger benchmarks on a server (with C++ version)
 //
-// Use `wee_alloc` as the global allocator.
+// Use `wee_alloc` as the global allocator, counted by `bench_alloc` for the
+// memory numbers of the benches, example and `circom_nova`.
 #[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+static ALLOC: CountingAllocator<wee_alloc::WeeAlloc> =
+    CountingAllocator::with(wee_alloc::WeeAlloc::INIT);
+
+pub type C1 = CircomCircuit<<G1 as Group>::Scalar>;
+pub type C2 = TrivialTestCircuit<<G2 as Group>::Scalar>;
 
-type C1 = CircomCircuit<<G1 as Group>::Scalar>;
-type C2 = TrivialTestCircuit<<G2 as Group>::Scalar>;
+pub type NovaPublicParams = PublicParams<G1, G2, C1, C2>;
+pub type NovaCompressedSNARK = CompressedSNARK<G1, G2, C1, C2, S1, S2>;
+
+/// A Spartan-compressed folding proof, with the verifier key it was checked
+/// against and what it took to produce it.
+pub struct CompressedProof {
+    pub snark: NovaCompressedSNARK,
+    pub vk: VerifierKey<G1, G2, C1, C2, S1, S2>,
+    /// Size of the bincode serialized, zlib compressed proof in bytes.
+    pub size: usize,
+    pub setup_time: Duration,
+    pub prove_time: Duration,
+    pub verify_time: Duration,
+}
 
 /// Creates a recursive SNARK, that proves the recursive (cyclic) calculation, of 4 values using Poseidon hashing function
 ///
-/// Panic
-///
-/// Code panics in case needed `poseidon_test_nova.r1cs` and/or `poseidon_test_nova.wasm` do no exist at specified paths
-pub fn recursive_hashing_proove(depth: usize) -> RecursiveSNARK<G1, G2, C1, C2> {
-    println! {"Using recursive depth: {:?} times depth_per_fold in circuit (default 10 or 100, check yourself! :D)", depth};
+/// The circuit is located through `CircomArtifacts::builder()`, i.e. `NOVA_BENCH_R1CS` /
+/// `NOVA_BENCH_WASM` or the example circuit compiled by `compile_vesta.sh`.
+pub fn recursive_hashing_proove(depth: usize) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
+    let artifacts = CircomArtifacts::builder().build()?;
+
+    recursive_hashing_proove_with(&artifacts, depth)
+}
+
+/// Same as `recursive_hashing_proove`, for the circuit at the given `artifacts` locations.
+pub fn recursive_hashing_proove_with(
+    artifacts: &CircomArtifacts,
+    depth: usize,
+) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
+    let (r1cs, pp) = public_params(artifacts);
 
-    let iteration_count = depth;
-    let root = current_dir().unwrap();
+    prove_chain(artifacts, r1cs, &pp, depth)
+}
+
+/// Same as `recursive_hashing_proove`, compressing the `RecursiveSNARK` into a
+/// `CompressedSNARK` with Spartan (see `compress`).
+pub fn recursive_hashing_proove_compressed(depth: usize) -> Result<CompressedProof, Error> {
+    let artifacts = CircomArtifacts::builder().build()?;
+
+    recursive_hashing_proove_compressed_with(&artifacts, depth)
+}
 
-    // has to be generated by circom before running the code
-    let circuit_file = root.join("./examples/poseidon/circom/poseidon_test_nova.r1cs");
-    let r1cs = load_r1cs(&FileLocation::PathBuf(circuit_file));
-    // has to be generated by circom before running the code
-    let witness_generator_wasm =
-        root.join("./examples/poseidon/circom/poseidon_test_nova_js/poseidon_test_nova.wasm");
+/// Same as `recursive_hashing_proove_compressed`, for the circuit at the given `artifacts`
+/// locations.
+pub fn recursive_hashing_proove_compressed_with(
+    artifacts: &CircomArtifacts,
+    depth: usize,
+) -> Result<CompressedProof, Error> {
+    let (r1cs, pp) = public_params(artifacts);
 
-    let step_in_vector = vec![0, 1, 2, 3];
+    let recursive_snark = prove_chain(artifacts, r1cs, &pp, depth)?;
+    let compressed = compress(&pp, &recursive_snark, depth, start_public_input())?;
+
+    eprintln!(
+        "CompressedSNARK::len {} bytes, setup {:?}, prove {:?}, verify {:?}",
+        compressed.size, compressed.setup_time, compressed.prove_time, compressed.verify_time
+    );
+
+    Ok(compressed)
+}
+
+/// Folds `depth` steps of the Poseidon chain from `start_public_input()` and checks the
+/// folded output against the native chain.
+fn prove_chain(
+    artifacts: &CircomArtifacts,
+    r1cs: R1CS<F1>,
+    pp: &NovaPublicParams,
+    depth: usize,
+) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
+    eprintln! {"Using recursive depth: {:?} times depth_per_fold in circuit (default 10 or 100, check yourself! :D)", depth};
+
+    let start_public_input = start_public_input();
 
     // We don't have any private inputs, so corresponding `HashMap`s are empty, thoough we still need to create and pass them
-    let mut private_inputs = Vec::new();
-    for _ in 0..iteration_count {
-        let private_input = HashMap::new();
-        private_inputs.push(private_input);
-    }
+    let timer_create_proof = start_timer!(|| "Create RecursiveSNARK");
+    let recursive_snark = fold(
+        artifacts,
+        r1cs,
+        pp,
+        start_public_input.clone(),
+        PrivateInputs::empty(depth),
+    )?;
+    end_timer!(timer_create_proof);
+
+    let timer_verify_snark = start_timer!(|| "Verify RecursiveSNARK");
+    verify_chain(pp, &recursive_snark, depth, start_public_input)?;
+    end_timer!(timer_verify_snark);
+
+    Ok(recursive_snark)
+}
+
+/// Folds the circuit at `artifacts` once per entry of `private_inputs`, starting from
+/// `start_public_input`, after validating the inputs against the circuit's declared
+/// private signals.
+pub fn prove_with_private_inputs(
+    artifacts: &CircomArtifacts,
+    start_public_input: Vec<F1>,
+    private_inputs: PrivateInputs,
+) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
+    // before the setup, so that invalid inputs fail fast
+    private_inputs.validate(&DeclaredInputs::from_artifacts(artifacts)?)?;
+
+    let (r1cs, pp) = public_params(artifacts);
+
+    fold_validated(artifacts, r1cs, &pp, start_public_input, private_inputs)
+}
 
-    let start_public_input = step_in_vector
-        .into_iter()
-        .map(|x| F1::from(x))
-        .collect::<Vec<_>>();
+/// Loads the r1cs at `artifacts` and creates the public parameters for it, printing the
+/// number of constraints and variables per step to stderr so stdout stays free for reports.
+pub fn public_params(artifacts: &CircomArtifacts) -> (R1CS<F1>, NovaPublicParams) {
+    let r1cs = load_r1cs(&FileLocation::PathBuf(artifacts.r1cs.clone()));
 
     // Creating public parameters
     let pp = create_public_params(r1cs.clone());
 
-    println!(
+    eprintln!(
         "Number of constraints per step (primary circuit): {}",
         pp.num_constraints().0
     );
-    println!(
+    eprintln!(
         "Number of constraints per step (secondary circuit): {}",
         pp.num_constraints().1
     );
 
-    println!(
+    eprintln!(
         "Number of variables per step (primary circuit): {}",
         pp.num_variables().0
     );
-    println!(
+    eprintln!(
         "Number of variables per step (secondary circuit): {}",
         pp.num_variables().1
     );
 
+    (r1cs, pp)
+}
+
+/// Same as `prove_with_private_inputs`, reusing an already loaded r1cs and its public parameters.
+pub fn fold(
+    artifacts: &CircomArtifacts,
+    r1cs: R1CS<F1>,
+    pp: &NovaPublicParams,
+    start_public_input: Vec<F1>,
+    private_inputs: PrivateInputs,
+) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
+    private_inputs.validate(&DeclaredInputs::from_artifacts(artifacts)?)?;
+
+    fold_validated(artifacts, r1cs, pp, start_public_input, private_inputs)
+}
+
+fn fold_validated(
+    artifacts: &CircomArtifacts,
+    r1cs: R1CS<F1>,
+    pp: &NovaPublicParams,
+    start_public_input: Vec<F1>,
+    private_inputs: PrivateInputs,
+) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
     // create a recursive SNARK
-    let recursive_snark = create_recursive_circuit(
-        FileLocation::PathBuf(witness_generator_wasm),
+    create_recursive_circuit(
+        FileLocation::PathBuf(artifacts.wasm.clone()),
         r1cs,
-        private_inputs,
-        start_public_input.clone(),
-        &pp,
+        private_inputs.into_inner(),
+        start_public_input,
+        pp,
     )
-    .unwrap();
+    .map_err(Error::WitnessGeneration)
+}
+
+/// Same as `fold`, proving from precomputed witnesses instead of running the WASM witness
+/// calculator, so that only folding is measured.
+pub fn fold_witnesses(
+    artifacts: &CircomArtifacts,
+    r1cs: R1CS<F1>,
+    pp: &NovaPublicParams,
+    start_public_input: Vec<F1>,
+    witnesses: Witnesses,
+) -> Result<RecursiveSNARK<G1, G2, C1, C2>, Error> {
+    witnesses.validate(&artifacts.header, &start_public_input)?;
+    if witnesses.is_empty() {
+        return Err(Error::InvalidWitness {
+            step: 0,
+            reason: "no witnesses given".to_string(),
+        });
+    }
+
+    let circuit_secondary = TrivialTestCircuit::default();
+    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+
+    let mut recursive_snark = None;
+    for witness in witnesses.into_inner() {
+        let circuit = CircomCircuit {
+            r1cs: r1cs.clone(),
+            witness: Some(witness),
+        };
+
+        recursive_snark = Some(
+            RecursiveSNARK::prove_step(
+                pp,
+                recursive_snark,
+                circuit,
+                circuit_secondary.clone(),
+                start_public_input.clone(),
+                z0_secondary.clone(),
+            )
+            .map_err(Error::Nova)?,
+        );
+    }
+
+    Ok(recursive_snark.unwrap())
+}
+
+/// Verifies a folded proof of the Poseidon example circuit and checks that its final `z_i`
+/// is the natively computed chain output.
+pub fn verify_chain(
+    pp: &NovaPublicParams,
+    recursive_snark: &RecursiveSNARK<G1, G2, C1, C2>,
+    num_steps: usize,
+    start_public_input: Vec<F1>,
+) -> Result<Vec<F1>, Error> {
+    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+
+    let (zn_primary, _) = recursive_snark
+        .verify(pp, num_steps, start_public_input.clone(), z0_secondary)
+        .map_err(Error::Nova)?;
+
+    check_chain_output(&start_public_input, num_steps, &zn_primary)?;
+
+    Ok(zn_primary)
+}
+
+/// Compares `zn_primary` against the native evaluation of `num_steps` folds from `z0`.
+pub fn check_chain_output(z0: &[F1], num_steps: usize, zn_primary: &[F1]) -> Result<(), Error> {
+    let expected = expected_chain_output(z0, num_steps)?;
+    if expected != zn_primary {
+        return Err(Error::ChainOutputMismatch {
+            expected,
+            actual: zn_primary.to_vec(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Compresses a folded proof of `num_steps` steps with Spartan: sets up the prover and
+/// verifier keys, proves, and verifies the compressed proof against `start_public_input`.
+pub fn compress(
+    pp: &NovaPublicParams,
+    recursive_snark: &RecursiveSNARK<G1, G2, C1, C2>,
+    num_steps: usize,
+    start_public_input: Vec<F1>,
+) -> Result<CompressedProof, Error> {
+    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
+
+    // pk - public key
+    // vk - verifying key
+    let start = Instant::now();
+    let (pk, vk) = NovaCompressedSNARK::setup(pp).map_err(Error::Nova)?;
+    let setup_time = start.elapsed();
+
+    let start = Instant::now();
+    let snark = NovaCompressedSNARK::prove(pp, &pk, recursive_snark).map_err(Error::Nova)?;
+    let prove_time = start.elapsed();
+
+    let start = Instant::now();
+    snark
+        .verify(&vk, num_steps, start_public_input, z0_secondary)
+        .map_err(Error::Nova)?;
+    let verify_time = start.elapsed();
+
+    Ok(CompressedProof {
+        size: encoded_len(&snark),
+        snark,
+        vk,
+        setup_time,
+        prove_time,
+        verify_time,
+    })
+}
 
-    recursive_snark
+/// Length of `value` serialized with bincode and compressed with zlib, as reported by the benches.
+pub fn encoded_len<T: Serialize>(value: &T) -> usize {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    bincode::serialize_into(&mut encoder, value).unwrap();
+    encoder.finish().unwrap().len()
 }
@@ -0,0 +1,259 @@
+use std::{env, fs, path::PathBuf};
+
+use ff::PrimeField;
+use num_bigint::BigUint;
+use num_traits::{Num, Zero};
+
+use crate::{artifacts::PRIMARY_PRIME, error::Error};
+
+/// Environment variable overriding the location of circomlib's `poseidon_constants.circom`.
+pub const POSEIDON_CONSTANTS_ENV: &str = "NOVA_BENCH_POSEIDON_CONSTANTS";
+
+/// circomlib 2.0.5, the version `examples/poseidon/circom/package.json` installs.
+pub const DEFAULT_POSEIDON_CONSTANTS: &str =
+    "../circom/circomlib/circuits/poseidon_constants.circom";
+
+/// Scalar field of BN254, which circomlib's Poseidon constants were generated for.
+pub const BN254_PRIME: &str = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+
+/// Hashes per fold in `poseidon_test_nova.circom` (`Main(10)`).
+pub const DEPTH_PER_FOLD: usize = 10;
+
+/// State width of the `PoseidonEx(4, 4)` used by the example circuit.
+pub const WIDTH: usize = 5;
+
+const N_ROUNDS_F: usize = 8;
+const N_ROUNDS_P: [usize; 16] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+/// Native evaluation of circomlib's `PoseidonEx` over the field of `modulus`.
+///
+/// circomlib evaluates Poseidon in its optimized form (the `C`, `S`, `M` and `P`
+/// constants). Over BN254 this is the circom-compatible Poseidon of light-poseidon,
+/// over any other prime circom reduces the same integers modulo that prime, which
+/// no longer is an equivalent form of any standard Poseidon instance. Comparing the
+/// folded output against this evaluation is therefore only meaningful when it
+/// mirrors circomlib step by step.
+#[derive(Clone, Debug)]
+pub struct CircomPoseidon {
+    pub modulus: BigUint,
+    pub width: usize,
+    c: Vec<BigUint>,
+    s: Vec<BigUint>,
+    m: Vec<Vec<BigUint>>,
+    p: Vec<Vec<BigUint>>,
+}
+
+impl CircomPoseidon {
+    /// Reads the constants for `width` from `NOVA_BENCH_POSEIDON_CONSTANTS` or the
+    /// circomlib checkout of the repository.
+    pub fn load(modulus: BigUint, width: usize) -> Result<Self, Error> {
+        let path = env::var_os(POSEIDON_CONSTANTS_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_POSEIDON_CONSTANTS)
+            });
+        if !path.is_file() {
+            return Err(Error::MissingArtifact(path));
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| Error::InvalidArtifact {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?;
+
+        Self::from_constants_str(&content, modulus, width)
+            .map_err(|reason| Error::InvalidArtifact { path, reason })
+    }
+
+    /// Instance used by the example circuit, over the primary curve's scalar field.
+    pub fn primary() -> Result<Self, Error> {
+        Self::load(BigUint::from_str_radix(PRIMARY_PRIME, 16).unwrap(), WIDTH)
+    }
+
+    pub fn from_constants_str(
+        content: &str,
+        modulus: BigUint,
+        width: usize,
+    ) -> Result<Self, String> {
+        if !(2..=17).contains(&width) {
+            return Err(format!("unsupported width {width}"));
+        }
+        let n_rounds_p = N_ROUNDS_P[width - 2];
+
+        let read = |name: &str, len: usize| {
+            let values = constants(content, name, width)?;
+            if values.len() != len {
+                return Err(format!(
+                    "{name}({width}) has {} values, expected {len}",
+                    values.len()
+                ));
+            }
+            Ok(values.into_iter().map(|v| v % &modulus).collect::<Vec<_>>())
+        };
+        let matrix = |values: Vec<BigUint>| {
+            values
+                .chunks(width)
+                .map(|row| row.to_vec())
+                .collect::<Vec<_>>()
+        };
+
+        Ok(CircomPoseidon {
+            c: read("POSEIDON_C", width * N_ROUNDS_F + n_rounds_p)?,
+            s: read("POSEIDON_S", n_rounds_p * (2 * width - 1))?,
+            m: matrix(read("POSEIDON_M", width * width)?),
+            p: matrix(read("POSEIDON_P", width * width)?),
+            modulus,
+            width,
+        })
+    }
+
+    /// `M` as indexed by circomlib, `M[j][i]` multiplies input `j` into output `i`.
+    pub fn mds(&self) -> &[Vec<BigUint>] {
+        &self.m
+    }
+
+    /// `PoseidonEx(width - 1, n_outs)`, template for template.
+    pub fn hash_ex(
+        &self,
+        inputs: &[BigUint],
+        initial_state: &BigUint,
+        n_outs: usize,
+    ) -> Vec<BigUint> {
+        let t = self.width;
+        assert_eq!(inputs.len(), t - 1);
+        assert!(n_outs <= t);
+
+        let n_rounds_p = N_ROUNDS_P[t - 2];
+        let half = N_ROUNDS_F / 2;
+
+        let sigma = |x: &BigUint| x.modpow(&BigUint::from(5u32), &self.modulus);
+        let ark = |state: &mut Vec<BigUint>, r: usize| {
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = (&*x + &self.c[i + r]) % &self.modulus;
+            }
+        };
+        let mix = |state: &[BigUint], matrix: &[Vec<BigUint>], n_outs: usize| {
+            (0..n_outs)
+                .map(|i| {
+                    state
+                        .iter()
+                        .zip(matrix)
+                        .fold(BigUint::zero(), |acc, (x, row)| acc + x * &row[i])
+                        % &self.modulus
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut state = [initial_state]
+            .into_iter()
+            .chain(inputs)
+            .map(|x| x % &self.modulus)
+            .collect::<Vec<_>>();
+
+        ark(&mut state, 0);
+        for r in 0..half - 1 {
+            state = state.iter().map(sigma).collect();
+            ark(&mut state, (r + 1) * t);
+            state = mix(&state, &self.m, t);
+        }
+
+        state = state.iter().map(sigma).collect();
+        ark(&mut state, half * t);
+        state = mix(&state, &self.p, t);
+
+        for r in 0..n_rounds_p {
+            state[0] = (sigma(&state[0]) + &self.c[(half + 1) * t + r]) % &self.modulus;
+
+            let s = &self.s[(2 * t - 1) * r..(2 * t - 1) * (r + 1)];
+            let first = state
+                .iter()
+                .zip(s)
+                .fold(BigUint::zero(), |acc, (x, s)| acc + x * s)
+                % &self.modulus;
+            let sigma_0 = std::mem::replace(&mut state[0], first);
+            for (x, s) in state.iter_mut().skip(1).zip(&s[t..]) {
+                *x = (&*x + &sigma_0 * s) % &self.modulus;
+            }
+        }
+
+        for r in 0..half - 1 {
+            state = state.iter().map(sigma).collect();
+            ark(&mut state, (half + 1) * t + n_rounds_p + r * t);
+            state = mix(&state, &self.m, t);
+        }
+
+        state = state.iter().map(sigma).collect();
+        mix(&state, &self.m, n_outs)
+    }
+
+    /// Output of `poseidon_test_nova.circom` after `num_hashes` chained
+    /// `PoseidonEx(4, 4)` with initial state 0, i.e. `z_i` after
+    /// `num_hashes / DEPTH_PER_FOLD` folds.
+    pub fn chain(&self, z0: &[BigUint], num_hashes: usize) -> Vec<BigUint> {
+        (0..num_hashes).fold(z0.to_vec(), |z, _| {
+            self.hash_ex(&z, &BigUint::zero(), self.width - 1)
+        })
+    }
+}
+
+/// `step_in = [0, 1, 2, 3]`, which the benches, the example and `recursive_hashing_proove`
+/// start the chain from.
+pub fn start_public_input<F: PrimeField>() -> Vec<F> {
+    (0..4).map(F::from).collect()
+}
+
+/// Expected `z_i` of the example circuit folded `num_steps` times from `z0`.
+pub fn expected_chain_output<F: PrimeField>(z0: &[F], num_steps: usize) -> Result<Vec<F>, Error> {
+    let poseidon = CircomPoseidon::primary()?;
+    let z0 = z0.iter().map(field_to_biguint).collect::<Vec<_>>();
+
+    Ok(poseidon
+        .chain(&z0, num_steps * DEPTH_PER_FOLD)
+        .iter()
+        .map(biguint_to_field)
+        .collect())
+}
+
+/// For fields with a little-endian repr, such as the pasta fields.
+pub fn field_to_biguint<F: PrimeField>(value: &F) -> BigUint {
+    BigUint::from_bytes_le(value.to_repr().as_ref())
+}
+
+pub fn biguint_to_field<F: PrimeField>(value: &BigUint) -> F {
+    let mut repr = F::Repr::default();
+    let bytes = value.to_bytes_le();
+    repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+    F::from_repr(repr).unwrap()
+}
+
+/// All hex literals of the `t == width` branch of `function name(t)`.
+fn constants(content: &str, name: &str, width: usize) -> Result<Vec<BigUint>, String> {
+    let start = content
+        .find(&format!("function {name}(t)"))
+        .ok_or_else(|| format!("function {name} not found"))?;
+    let end = content[start + 1..]
+        .find("function ")
+        .map_or(content.len(), |end| start + 1 + end);
+    let function = &content[start..end];
+
+    let branch = function
+        .match_indices("t")
+        .map(|(i, _)| i + 1)
+        .find(|&i| {
+            let rest = function[i..].trim_start();
+            rest.strip_prefix("==")
+                .and_then(|rest| rest.trim_start().strip_prefix(&width.to_string()))
+                .is_some_and(|rest| rest.trim_start().starts_with(')'))
+        })
+        .ok_or_else(|| format!("{name} has no branch for t == {width}"))?;
+    let branch = &function[branch..];
+    let branch = &branch[..branch.find("} else").unwrap_or(branch.len())];
+
+    branch
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|token| token.strip_prefix("0x"))
+        .map(|hex| BigUint::from_str_radix(hex, 16).map_err(|e| format!("{name}: {e}")))
+        .collect()
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use num_bigint::BigUint;
+use num_traits::Num;
+use serde_json::Value;
+
+use crate::{
+    artifacts::{CircomArtifacts, PRIMARY_PRIME},
+    error::Error,
+};
+
+/// Private inputs of a single folding step, keyed by circom signal name.
+pub type StepInputs = HashMap<String, Value>;
+
+/// Per-step private inputs for `create_recursive_circuit`, one entry per step.
+#[derive(Clone, Debug, Default)]
+pub struct PrivateInputs(pub Vec<StepInputs>);
+
+impl PrivateInputs {
+    /// Inputs for a circuit without private signals folded `num_steps` times.
+    pub fn empty(num_steps: usize) -> Self {
+        PrivateInputs(vec![HashMap::new(); num_steps])
+    }
+
+    /// Reads either a JSON array with one object per step or a JSONL stream
+    /// with one object per line.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::InvalidArtifact {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+
+        Self::from_json_str(&content).map_err(|reason| Error::InvalidArtifact {
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+
+    pub fn from_json_str(content: &str) -> Result<Self, String> {
+        if content.trim_start().starts_with('[') {
+            return serde_json::from_str(content)
+                .map(PrivateInputs)
+                .map_err(|e| e.to_string());
+        }
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {e}", i + 1)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(PrivateInputs)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks every step against the circuit's private input signals: no unknown
+    /// or missing signals, matching array sizes, and field elements given as
+    /// non-negative integers or decimal strings below the prime.
+    pub fn validate(&self, declared: &DeclaredInputs) -> Result<(), Error> {
+        let prime = BigUint::from_str_radix(PRIMARY_PRIME, 16).unwrap();
+
+        for (step, inputs) in self.0.iter().enumerate() {
+            let invalid = |reason: String| Error::InvalidPrivateInputs { step, reason };
+
+            let mut total = 0;
+            for (name, value) in inputs {
+                let mut count = 0;
+                count_elements(value, &prime, &mut count)
+                    .map_err(|reason| invalid(format!("signal `{name}`: {reason}")))?;
+
+                if let Some(signals) = &declared.signals {
+                    let expected = signals
+                        .get(name)
+                        .ok_or_else(|| invalid(format!("unknown private signal `{name}`")))?;
+                    if *expected != count {
+                        return Err(invalid(format!(
+                            "signal `{name}` has {count} elements, the circuit declares {expected}"
+                        )));
+                    }
+                }
+                total += count;
+            }
+
+            if let Some(signals) = &declared.signals {
+                if let Some(missing) = signals.keys().find(|name| !inputs.contains_key(*name)) {
+                    return Err(invalid(format!("missing private signal `{missing}`")));
+                }
+            }
+
+            if total != declared.num_private {
+                return Err(invalid(format!(
+                    "{total} private values given, the circuit declares {}",
+                    declared.num_private
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> Vec<StepInputs> {
+        self.0
+    }
+}
+
+fn count_elements(value: &Value, prime: &BigUint, count: &mut usize) -> Result<(), String> {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                count_elements(value, prime, count)?;
+            }
+            return Ok(());
+        }
+        Value::Number(n) if n.is_u64() => {}
+        Value::String(s) => {
+            let n = BigUint::from_str_radix(s, 10)
+                .map_err(|_| format!("`{s}` is not a decimal integer"))?;
+            if &n >= prime {
+                return Err(format!("`{s}` is not below the field prime"));
+            }
+        }
+        other => return Err(format!("unsupported value `{other}`")),
+    }
+    *count += 1;
+    Ok(())
+}
+
+/// Private input signals declared by a compiled circuit.
+#[derive(Clone, Debug)]
+pub struct DeclaredInputs {
+    /// Number of private input wires, from the r1cs header.
+    pub num_private: usize,
+    /// Signal name to number of elements, from the `.sym` file next to the r1cs
+    /// when circom was run with `--sym`.
+    pub signals: Option<BTreeMap<String, usize>>,
+}
+
+impl DeclaredInputs {
+    pub fn from_artifacts(artifacts: &CircomArtifacts) -> Result<Self, Error> {
+        let header = &artifacts.header;
+        let num_private = header.num_prv_in as usize;
+
+        let sym = artifacts.r1cs.with_extension("sym");
+        if !sym.is_file() {
+            return Ok(DeclaredInputs {
+                num_private,
+                signals: None,
+            });
+        }
+
+        let content = fs::read_to_string(&sym).map_err(|e| Error::InvalidArtifact {
+            path: sym.clone(),
+            reason: e.to_string(),
+        })?;
+
+        // wire 0 is the constant one, followed by outputs, public and private inputs
+        let first = 1 + header.num_pub_out as i64 + header.num_pub_in as i64;
+        let private_wires = first..first + num_private as i64;
+
+        let mut signals = BTreeMap::new();
+        for line in content.lines() {
+            let fields = line.splitn(4, ',').collect::<Vec<_>>();
+            let (Some(wire), Some(name)) = (fields.get(1), fields.get(3)) else {
+                continue;
+            };
+            let Ok(wire) = wire.parse::<i64>() else {
+                continue;
+            };
+            if !private_wires.contains(&wire) {
+                continue;
+            }
+            if let Some(name) = name.strip_prefix("main.") {
+                let base = name.split('[').next().unwrap_or(name);
+                *signals.entry(base.to_string()).or_insert(0) += 1;
+            }
+        }
+
+        Ok(DeclaredInputs {
+            num_private,
+            signals: Some(signals),
+        })
+    }
+}
@@ -0,0 +1,373 @@
+#[cfg(test)]
+mod test {
+    use crate::artifacts::{CircomArtifacts, R1csHeader, PRIMARY_PRIME};
+    use crate::check_chain_output;
+    use crate::error::Error;
+    use crate::poseidon::{expected_chain_output, CircomPoseidon, BN254_PRIME, WIDTH};
+    use crate::private_inputs::{DeclaredInputs, PrivateInputs};
+    use crate::witness::{read_wtns, Witnesses};
+
+    use ark_bn254::Fr;
+    use light_poseidon::{parameters::bn254_x5::get_poseidon_parameters, Poseidon, PoseidonHasher};
+    use nova_scotia::F1;
+
+    use num_bigint::BigUint;
+    use num_traits::{Num, Zero};
+    use std::{fs, path::PathBuf};
+
+    /// Writes an r1cs file holding only a header section for `prime`.
+    fn write_r1cs_header(name: &str, prime: &BigUint) -> PathBuf {
+        let mut prime_bytes = prime.to_bytes_le();
+        prime_bytes.resize(32, 0);
+
+        let mut header = vec![];
+        header.extend(32u32.to_le_bytes());
+        header.extend(&prime_bytes);
+        header.extend(10u32.to_le_bytes()); // wires
+        header.extend(4u32.to_le_bytes()); // public outputs
+        header.extend(4u32.to_le_bytes()); // public inputs
+        header.extend(0u32.to_le_bytes()); // private inputs
+        header.extend(10u64.to_le_bytes()); // labels
+        header.extend(3u32.to_le_bytes()); // constraints
+
+        let mut bytes = b"r1cs".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        // an empty constraints section before the header
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(0u64.to_le_bytes());
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((header.len() as u64).to_le_bytes());
+        bytes.extend(header);
+
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn primary_prime() -> BigUint {
+        BigUint::from_str_radix(PRIMARY_PRIME, 16).unwrap()
+    }
+
+    #[test]
+    fn test_r1cs_header() {
+        let path = write_r1cs_header("nova_bench_header.r1cs", &primary_prime());
+
+        let header = R1csHeader::read(&path).unwrap();
+        assert_eq!(header.prime, primary_prime());
+        assert_eq!(header.num_pub_out, 4);
+        assert_eq!(header.num_pub_in, 4);
+        assert_eq!(header.num_constraints, 3);
+    }
+
+    #[test]
+    fn test_missing_artifact() {
+        let res = CircomArtifacts::new("does/not/exist.r1cs", "does/not/exist.wasm");
+        assert!(matches!(res, Err(Error::MissingArtifact(_))));
+    }
+
+    #[test]
+    fn test_wrong_prime() {
+        // pallas base field, i.e. the circuit was compiled with `--prime pallas`
+        let pallas = BigUint::from_str_radix(
+            "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+            16,
+        )
+        .unwrap();
+        let r1cs = write_r1cs_header("nova_bench_pallas.r1cs", &pallas);
+        let wasm = std::env::temp_dir().join("nova_bench_wrong_prime.wasm");
+        fs::write(&wasm, b"").unwrap();
+
+        let res = CircomArtifacts::new(r1cs, wasm);
+        assert!(matches!(res, Err(Error::WrongPrime { .. })));
+    }
+
+    #[test]
+    fn test_invalid_r1cs() {
+        let r1cs = std::env::temp_dir().join("nova_bench_invalid.r1cs");
+        fs::write(&r1cs, b"not an r1cs").unwrap();
+
+        let res = R1csHeader::read(&r1cs);
+        assert!(matches!(res, Err(Error::InvalidArtifact { .. })));
+    }
+
+    fn sha256_declared_inputs() -> DeclaredInputs {
+        DeclaredInputs {
+            num_private: 64,
+            signals: Some([("in".to_string(), 32), ("hash".to_string(), 32)].into()),
+        }
+    }
+
+    fn sha256_step(hash: &str) -> String {
+        let in_ = vec!["\"0\""; 32].join(",");
+        let hash = vec![format!("\"{hash}\""); 32].join(",");
+        format!("{{\"in\": [{in_}], \"hash\": [{hash}]}}")
+    }
+
+    #[test]
+    fn test_private_inputs_array_and_jsonl() {
+        let steps = [sha256_step("102"), sha256_step("104")];
+
+        let array = PrivateInputs::from_json_str(&format!("[{}]", steps.join(","))).unwrap();
+        let jsonl = PrivateInputs::from_json_str(&steps.join("\n")).unwrap();
+
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.0, jsonl.0);
+        array.validate(&sha256_declared_inputs()).unwrap();
+    }
+
+    #[test]
+    fn test_private_inputs_big_decimal() {
+        let below = "28948022309329048855892746252171976963363056481941647379679742748393362948096";
+        let prime = "28948022309329048855892746252171976963363056481941647379679742748393362948097";
+
+        let inputs = PrivateInputs::from_json_str(&sha256_step(below)).unwrap();
+        inputs.validate(&sha256_declared_inputs()).unwrap();
+
+        let inputs = PrivateInputs::from_json_str(&sha256_step(prime)).unwrap();
+        assert!(matches!(
+            inputs.validate(&sha256_declared_inputs()),
+            Err(Error::InvalidPrivateInputs { step: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_private_inputs_mismatch() {
+        let declared = sha256_declared_inputs();
+
+        let unknown = PrivateInputs::from_json_str(r#"{"foo": 1}"#).unwrap();
+        assert!(matches!(
+            unknown.validate(&declared),
+            Err(Error::InvalidPrivateInputs { .. })
+        ));
+
+        let wrong_size = PrivateInputs::from_json_str(r#"{"in": [1, 2], "hash": [3]}"#).unwrap();
+        assert!(matches!(
+            wrong_size.validate(&declared),
+            Err(Error::InvalidPrivateInputs { .. })
+        ));
+
+        let step = sha256_step("1");
+        let missing = format!("{step}\n{}", r#"{"in": [0]}"#);
+        let missing = PrivateInputs::from_json_str(&missing).unwrap();
+        assert!(matches!(
+            missing.validate(&declared),
+            Err(Error::InvalidPrivateInputs { step: 1, .. })
+        ));
+
+        assert!(PrivateInputs::empty(3)
+            .validate(&DeclaredInputs {
+                num_private: 0,
+                signals: None,
+            })
+            .is_ok());
+    }
+
+    fn bn254_poseidon(width: usize) -> CircomPoseidon {
+        CircomPoseidon::load(BigUint::from_str_radix(BN254_PRIME, 16).unwrap(), width).unwrap()
+    }
+
+    /// circomlib's optimized Poseidon and light-poseidon's circom-compatible
+    /// Poseidon agree on BN254, the field circomlib's constants were generated for.
+    #[test]
+    fn test_circom_poseidon_matches_light_poseidon() {
+        for width in [3, WIDTH] {
+            let inputs = (1..width as u64).map(BigUint::from).collect::<Vec<_>>();
+            let circom = bn254_poseidon(width).hash_ex(&inputs, &BigUint::zero(), 1);
+
+            let mut light = Poseidon::<Fr>::new_circom(width - 1).unwrap();
+            let light = light
+                .hash(&inputs.iter().cloned().map(Fr::from).collect::<Vec<_>>())
+                .unwrap();
+
+            assert_eq!(circom[0], BigUint::from(light));
+        }
+
+        // the well known circomlib `Poseidon([1, 2])`
+        let expected = "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a";
+        let inputs = [BigUint::from(1u32), BigUint::from(2u32)];
+        assert_eq!(
+            bn254_poseidon(3).hash_ex(&inputs, &BigUint::zero(), 1)[0],
+            BigUint::from_str_radix(expected, 16).unwrap()
+        );
+    }
+
+    /// `poseidon_test_nova.circom` uses `PoseidonEx(4, 4)`: width 5, x^5 S-box,
+    /// 8 full and 60 partial rounds, with the BN254 parameter set of light-poseidon
+    /// (circomlib's `M[j][i]` is light-poseidon's `mds[i][j]`).
+    ///
+    /// The circuit is compiled with `--prime vesta`, so circom evaluates these
+    /// BN254 constants in the pallas scalar field. The result is neither BN254
+    /// Poseidon nor a standard Poseidon instance over pallas, and can only be
+    /// reproduced natively by `CircomPoseidon` over `PRIMARY_PRIME`.
+    #[test]
+    fn test_circom_poseidon_parameter_set() {
+        let params = get_poseidon_parameters::<Fr>(WIDTH as u8).unwrap();
+        assert_eq!(params.width, WIDTH);
+        assert_eq!(params.full_rounds, 8);
+        assert_eq!(params.partial_rounds, 60);
+        assert_eq!(params.alpha, 5);
+
+        let circom = bn254_poseidon(WIDTH);
+        for (i, row) in params.mds.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert_eq!(BigUint::from(*value), circom.mds()[j][i]);
+            }
+        }
+
+        let z0 = (0..4u32).map(BigUint::from).collect::<Vec<_>>();
+        let pallas = CircomPoseidon::primary().unwrap();
+        assert_ne!(pallas.chain(&z0, 1), circom.chain(&z0, 1));
+    }
+
+    #[test]
+    fn test_expected_chain_output() {
+        let z0 = (0..4u64).map(F1::from).collect::<Vec<_>>();
+        assert_eq!(expected_chain_output(&z0, 0).unwrap(), z0);
+
+        // one fold of `Main(10)` from step_in [0, 1, 2, 3]
+        let expected = [
+            "34f1caf81191fc41ecd9f88f6855afc73ccba9eb04fdb61b4b5cb9b355c82f52",
+            "051d363c57bdc0d57fd285ddd5fcc767b8f1490b44c6f504e11e5cfac3e8a16a",
+            "0c2afd4f85c1f0eac8f279bfd67188737138c169ff41da1960cc3f12e18da1bb",
+            "1db7d1a52dec69aa168aabe8788a9d93e292f89b374d47855dac09d6d76c2f1d",
+        ];
+        let z1 = expected_chain_output(&z0, 1).unwrap();
+        let z1_hex = z1
+            .iter()
+            .map(|z| format!("{:064x}", crate::poseidon::field_to_biguint(z)))
+            .collect::<Vec<_>>();
+        assert_eq!(z1_hex, expected);
+
+        assert!(check_chain_output(&z0, 1, &z1).is_ok());
+        assert!(matches!(
+            check_chain_output(&z0, 2, &z1),
+            Err(Error::ChainOutputMismatch { .. })
+        ));
+    }
+
+    /// Encodes one witness in the circom `.wtns` format.
+    fn wtns_bytes(prime: &BigUint, values: &[u64]) -> Vec<u8> {
+        let mut prime_bytes = prime.to_bytes_le();
+        prime_bytes.resize(32, 0);
+
+        let mut bytes = b"wtns".to_vec();
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(40u64.to_le_bytes());
+        bytes.extend(32u32.to_le_bytes());
+        bytes.extend(&prime_bytes);
+        bytes.extend((values.len() as u32).to_le_bytes());
+
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend((32 * values.len() as u64).to_le_bytes());
+        for value in values {
+            let mut value = value.to_le_bytes().to_vec();
+            value.resize(32, 0);
+            bytes.extend(value);
+        }
+        bytes
+    }
+
+    fn write_wtns(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    /// Header of a circuit with 10 wires: one, 4 outputs, 4 inputs and 1 internal.
+    fn wtns_header() -> R1csHeader {
+        R1csHeader {
+            prime: primary_prime(),
+            num_wires: 10,
+            num_pub_out: 4,
+            num_pub_in: 4,
+            num_prv_in: 0,
+            num_labels: 10,
+            num_constraints: 3,
+        }
+    }
+
+    fn to_f1(values: &[u64]) -> Vec<F1> {
+        values.iter().map(|v| F1::from(*v)).collect()
+    }
+
+    #[test]
+    fn test_read_wtns() {
+        let step_0 = [1, 5, 6, 7, 8, 1, 2, 3, 4, 9];
+        let step_1 = [1, 9, 10, 11, 12, 5, 6, 7, 8, 0];
+
+        let single = write_wtns(
+            "nova_bench_step_0.wtns",
+            &wtns_bytes(&primary_prime(), &step_0),
+        );
+        assert_eq!(read_wtns(&single).unwrap(), vec![to_f1(&step_0)]);
+
+        let archive = [
+            wtns_bytes(&primary_prime(), &step_0),
+            wtns_bytes(&primary_prime(), &step_1),
+        ]
+        .concat();
+        let archive = write_wtns("nova_bench_steps.wtns", &archive);
+
+        let witnesses = Witnesses::from_files(&[archive]).unwrap();
+        assert_eq!(witnesses.0, vec![to_f1(&step_0), to_f1(&step_1)]);
+        witnesses
+            .validate(&wtns_header(), &to_f1(&[1, 2, 3, 4]))
+            .unwrap();
+
+        // step_out of the last step becomes step_in of the next
+        assert!(matches!(
+            Witnesses(vec![to_f1(&step_1), to_f1(&step_0)])
+                .validate(&wtns_header(), &to_f1(&[5, 6, 7, 8])),
+            Err(Error::InvalidWitness { step: 1, .. })
+        ));
+        assert!(matches!(
+            witnesses.validate(&wtns_header(), &to_f1(&[0, 0, 0, 0])),
+            Err(Error::InvalidWitness { step: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_wtns_length_mismatch() {
+        let short = write_wtns(
+            "nova_bench_short.wtns",
+            &wtns_bytes(&primary_prime(), &[1, 5, 6, 7, 8, 1, 2, 3, 4]),
+        );
+
+        let witnesses = Witnesses::from_files(&[short]).unwrap();
+        assert!(matches!(
+            witnesses.validate(&wtns_header(), &to_f1(&[1, 2, 3, 4])),
+            Err(Error::WitnessLengthMismatch {
+                step: 0,
+                expected: 10,
+                found: 9
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_wtns() {
+        let bn254 = BigUint::from_str_radix(BN254_PRIME, 16).unwrap();
+        let wrong_prime = write_wtns("nova_bench_bn254.wtns", &wtns_bytes(&bn254, &[1]));
+        assert!(matches!(
+            read_wtns(&wrong_prime),
+            Err(Error::WrongPrime { .. })
+        ));
+
+        let mut bytes = wtns_bytes(&primary_prime(), &[1, 2]);
+        bytes.truncate(bytes.len() - 1);
+        let truncated = write_wtns("nova_bench_truncated.wtns", &bytes);
+        assert!(matches!(
+            read_wtns(&truncated),
+            Err(Error::InvalidArtifact { .. })
+        ));
+
+        assert!(matches!(
+            read_wtns(&std::env::temp_dir().join("nova_bench_missing.wtns")),
+            Err(Error::MissingArtifact(_))
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use ff::PrimeField;
+use nova_scotia::F1;
+use num_bigint::BigUint;
+use num_traits::Num;
+
+use crate::{
+    artifacts::{read_u32, read_u64, R1csHeader, PRIMARY_PRIME},
+    error::Error,
+};
+
+/// Environment variable pointing the benches to a precomputed `.wtns` archive.
+pub const WTNS_ENV: &str = "NOVA_BENCH_WTNS";
+
+/// Precomputed witnesses, one per folding step.
+#[derive(Clone, Debug, Default)]
+pub struct Witnesses(pub Vec<Vec<F1>>);
+
+impl Witnesses {
+    /// Reads circom `.wtns` files in step order. A file holds either the witness
+    /// of a single step or a multi-step archive of several witnesses back to
+    /// back, e.g. `cat step_*.wtns > steps.wtns`.
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self, Error> {
+        let mut witnesses = vec![];
+        for path in paths {
+            witnesses.extend(read_wtns(path.as_ref())?);
+        }
+
+        Ok(Witnesses(witnesses))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks every witness against the r1cs: one value per wire, the constant
+    /// one first, and `step_in` equal to `start_public_input` for the first step
+    /// and to the previous `step_out` afterwards.
+    pub fn validate(&self, header: &R1csHeader, start_public_input: &[F1]) -> Result<(), Error> {
+        let num_outputs = header.num_pub_out as usize;
+        let inputs = 1 + num_outputs..1 + num_outputs + header.num_pub_in as usize;
+
+        let mut step_in = start_public_input;
+        for (step, witness) in self.0.iter().enumerate() {
+            let invalid = |reason: &str| Error::InvalidWitness {
+                step,
+                reason: reason.to_string(),
+            };
+
+            if witness.len() != header.num_wires as usize {
+                return Err(Error::WitnessLengthMismatch {
+                    step,
+                    expected: header.num_wires as usize,
+                    found: witness.len(),
+                });
+            }
+            if witness[0] != F1::from(1) {
+                return Err(invalid("the first wire is not the constant one"));
+            }
+            if witness[inputs.clone()] != *step_in {
+                return Err(invalid(if step == 0 {
+                    "step_in does not match the start public input"
+                } else {
+                    "step_in does not match step_out of the previous step"
+                }));
+            }
+
+            step_in = &witness[1..1 + num_outputs];
+        }
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> Vec<Vec<F1>> {
+        self.0
+    }
+}
+
+/// Reads all witnesses of a `.wtns` file.
+pub fn read_wtns(path: &Path) -> Result<Vec<Vec<F1>>, Error> {
+    let invalid = |e: io::Error| Error::InvalidArtifact {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    };
+
+    if !path.is_file() {
+        return Err(Error::MissingArtifact(path.to_path_buf()));
+    }
+    let mut reader = BufReader::new(File::open(path).map_err(invalid)?);
+
+    let len = reader.seek(SeekFrom::End(0)).map_err(invalid)?;
+    reader.seek(SeekFrom::Start(0)).map_err(invalid)?;
+
+    let expected = BigUint::from_str_radix(PRIMARY_PRIME, 16).unwrap();
+    let mut witnesses = vec![];
+    while reader.stream_position().map_err(invalid)? < len {
+        let (prime, values) = read_wtns_from(&mut reader).map_err(invalid)?;
+        if prime != expected {
+            return Err(Error::WrongPrime {
+                path: path.to_path_buf(),
+                expected,
+                found: prime,
+            });
+        }
+
+        witnesses.push(
+            values
+                .into_iter()
+                .map(|bytes| {
+                    let mut repr = <F1 as PrimeField>::Repr::default();
+                    repr.as_mut().copy_from_slice(&bytes);
+                    Option::from(F1::from_repr(repr)).ok_or_else(|| {
+                        invalid(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "witness value is not below the prime",
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+    }
+
+    Ok(witnesses)
+}
+
+/// Reads one witness, returning its prime and little-endian values.
+fn read_wtns_from<R: Read>(reader: &mut R) -> io::Result<(BigUint, Vec<Vec<u8>>)> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"wtns" {
+        return Err(invalid("not a wtns file"));
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+
+    let mut sections = HashMap::new();
+    for _ in 0..num_sections {
+        let section_type = read_u32(reader)?;
+        let section_size = read_u64(reader)?;
+        let mut section = vec![0u8; section_size as usize];
+        reader.read_exact(&mut section)?;
+        sections.insert(section_type, section);
+    }
+
+    let mut header = sections
+        .get(&1)
+        .ok_or_else(|| invalid("wtns header section not found"))?
+        .as_slice();
+    let field_size = read_u32(&mut header)? as usize;
+    if field_size != 32 {
+        return Err(invalid("wtns field size is not 32 bytes"));
+    }
+    let mut prime = vec![0u8; field_size];
+    header.read_exact(&mut prime)?;
+    let num_values = read_u32(&mut header)? as usize;
+
+    let values = sections
+        .get(&2)
+        .ok_or_else(|| invalid("wtns values section not found"))?;
+    if values.len() != num_values * field_size {
+        return Err(invalid("wtns values section does not match its header"));
+    }
+
+    Ok((
+        BigUint::from_bytes_le(&prime),
+        values.chunks(field_size).map(<[u8]>::to_vec).collect(),
+    ))
+}
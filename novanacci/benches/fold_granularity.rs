@@ -0,0 +1,156 @@
+use core::time::Duration;
+use criterion::*;
+use novanacci::{
+    circuit::LinearRecurrence,
+    prove_verify::{prove, public_params, verify},
+};
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+    time::Instant,
+};
+
+criterion_group! {
+name = fold_granularity;
+config = Criterion::default().warm_up_time(Duration::from_millis(3000));
+targets = bench_fold_granularity
+}
+
+criterion_main!(fold_granularity);
+
+/// Fibonacci index proven by every case, `num_steps * num_iters_per_step`.
+const TOTAL_INDEX: usize = 4096;
+
+const ITERS_PER_STEP: [usize; 5] = [16, 64, 256, 1024, 4096];
+
+#[derive(Default)]
+struct Row {
+    num_steps: usize,
+    num_iters_per_step: usize,
+    constraints_primary: usize,
+    constraints_secondary: usize,
+    pp_bytes: usize,
+    setup: Duration,
+    prove: Duration,
+    verify: Duration,
+}
+
+fn mean(total: Duration, iters: u64) -> Duration {
+    total / iters.max(1) as u32
+}
+
+fn bench_fold_granularity(c: &mut Criterion) {
+    let fibonacci = LinearRecurrence::fibonacci();
+    let mut rows = vec![];
+
+    for num_iters_per_step in ITERS_PER_STEP {
+        let num_steps = TOTAL_INDEX / num_iters_per_step;
+
+        let mut group = c.benchmark_group(format!(
+            "Novanacci-F({TOTAL_INDEX})-num-steps-{num_steps}-iters-per-step-{num_iters_per_step}"
+        ));
+        group.sample_size(10);
+
+        let mut row = Row {
+            num_steps,
+            num_iters_per_step,
+            ..Default::default()
+        };
+
+        let (mut total, mut iters) = (Duration::ZERO, 0);
+        group.bench_function("Setup", |b| {
+            b.iter_custom(|n| {
+                let start = Instant::now();
+                for _ in 0..n {
+                    black_box(public_params(&fibonacci, num_iters_per_step));
+                }
+                let elapsed = start.elapsed();
+                (total, iters) = (total + elapsed, iters + n);
+                elapsed
+            })
+        });
+        row.setup = mean(total, iters);
+
+        let pp = public_params(&fibonacci, num_iters_per_step);
+        row.constraints_primary = pp.num_constraints().0;
+        row.constraints_secondary = pp.num_constraints().1;
+        row.pp_bytes = bincode::serialize(&pp).unwrap().len();
+
+        let (mut total, mut iters) = (Duration::ZERO, 0);
+        group.bench_function("Prove", |b| {
+            b.iter_custom(|n| {
+                let start = Instant::now();
+                for _ in 0..n {
+                    black_box(prove(&pp, &fibonacci, num_steps, num_iters_per_step).unwrap());
+                }
+                let elapsed = start.elapsed();
+                (total, iters) = (total + elapsed, iters + n);
+                elapsed
+            })
+        });
+        row.prove = mean(total, iters);
+
+        let proof = prove(&pp, &fibonacci, num_steps, num_iters_per_step).unwrap();
+
+        let (mut total, mut iters) = (Duration::ZERO, 0);
+        group.bench_function("Verify", |b| {
+            b.iter_custom(|n| {
+                let start = Instant::now();
+                for _ in 0..n {
+                    verify(&pp, &fibonacci, &proof).unwrap();
+                }
+                let elapsed = start.elapsed();
+                (total, iters) = (total + elapsed, iters + n);
+                elapsed
+            })
+        });
+        row.verify = mean(total, iters);
+        group.finish();
+
+        println!(
+            "Public params size for {num_iters_per_step} iterations per step: {} bytes",
+            row.pp_bytes
+        );
+        rows.push(row);
+    }
+
+    write_csv(&rows);
+}
+
+/// Writes the per-case means to `NOVANACCI_BENCH_CSV`, or
+/// `target/fold_granularity.csv` by default.
+fn write_csv(rows: &[Row]) {
+    let path = env::var("NOVANACCI_BENCH_CSV")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("target/fold_granularity.csv"));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+
+    let mut file = File::create(&path).unwrap();
+    writeln!(
+        file,
+        "total_index,num_steps,num_iters_per_step,constraints_primary,constraints_secondary,pp_bytes,setup_ms,prove_ms,verify_ms"
+    )
+    .unwrap();
+    for row in rows {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{:.3},{:.3},{:.3}",
+            TOTAL_INDEX,
+            row.num_steps,
+            row.num_iters_per_step,
+            row.constraints_primary,
+            row.constraints_secondary,
+            row.pp_bytes,
+            row.setup.as_secs_f64() * 1e3,
+            row.prove.as_secs_f64() * 1e3,
+            row.verify.as_secs_f64() * 1e3,
+        )
+        .unwrap();
+    }
+
+    println!("Fold granularity results written to {}", path.display());
+}
@@ -0,0 +1,76 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    circuit::LinearRecurrence,
+    prove_verify::{public_params, RecurrenceProof, RecurrencePublicParams},
+};
+
+pub const DEFAULT_PARAMS_DIR: &str = "params";
+
+impl RecurrenceProof {
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> bincode::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> bincode::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        bincode::deserialize_from(reader)
+    }
+}
+
+pub fn public_params_path<P: AsRef<Path>>(params_dir: P, num_iters_per_step: usize) -> PathBuf {
+    params_dir
+        .as_ref()
+        .join(format!("fibonacci_pp_{num_iters_per_step}.bin"))
+}
+
+/// Sidecar holding the hex SHA-256 of the cached public params at `path`.
+fn checksum_path(path: &Path) -> PathBuf {
+    path.with_extension("bin.sha256")
+}
+
+/// Loads the Fibonacci public parameters for `num_iters_per_step` from
+/// `params_dir`, running the setup and caching the result on a miss.
+///
+/// The cache is only used when its SHA-256 matches the sidecar written next to
+/// it, otherwise loading fails rather than verifying against corrupted
+/// parameters; delete both files to regenerate them. Anyone who can replace the
+/// cache can replace the sidecar too, so this is no protection against
+/// substituted parameters.
+///
+/// Returns the parameters and whether they came from the cache.
+pub fn load_or_setup_public_params<P: AsRef<Path>>(
+    params_dir: P,
+    num_iters_per_step: usize,
+) -> bincode::Result<(RecurrencePublicParams, bool)> {
+    let path = public_params_path(&params_dir, num_iters_per_step);
+
+    if path.exists() {
+        let bytes = fs::read(&path)?;
+        let expected = fs::read_to_string(checksum_path(&path))?;
+        if hex::encode(Sha256::digest(&bytes)) != expected.trim() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} does not match its checksum", path.display()),
+            )
+            .into());
+        }
+        return Ok((bincode::deserialize(&bytes)?, true));
+    }
+
+    let pp = public_params(&LinearRecurrence::fibonacci(), num_iters_per_step);
+
+    fs::create_dir_all(params_dir)?;
+    let bytes = bincode::serialize(&pp)?;
+    fs::write(&path, &bytes)?;
+    fs::write(checksum_path(&path), hex::encode(Sha256::digest(&bytes)))?;
+
+    Ok((pp, false))
+}
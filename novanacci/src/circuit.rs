@@ -5,43 +5,129 @@ use nova_snark::traits::circuit::StepCircuit;
 #[warn(unused_imports)]
 use crate::prove_verify::prove_and_verify;
 
+/// k-term linear recurrence `s(n+k) = c_0 * s(n) + ... + c_{k-1} * s(n+k-1)`.
+///
+/// `coefficients` and `initial` are both ordered from the oldest term to the newest.
 #[derive(Clone, Debug)]
-pub struct FibonacciObject<F: PrimeField> {
-    pub x: F,
-    pub y: F,
-    pub x_next: F,
-    pub y_next: F,
+pub struct LinearRecurrence<F: PrimeField> {
+    pub coefficients: Vec<F>,
+    pub initial: Vec<F>,
 }
 
-impl<F: PrimeField> FibonacciObject<F> {
-    pub fn new(num_iters: usize, x_0: &F, y_0: &F) -> (Vec<F>, Vec<Self>) {
+impl<F: PrimeField> LinearRecurrence<F> {
+    pub fn new(coefficients: Vec<F>, initial: Vec<F>) -> Self {
+        assert!(!coefficients.is_empty());
+        assert_eq!(coefficients.len(), initial.len());
+
+        Self {
+            coefficients,
+            initial,
+        }
+    }
+
+    /// `F(n+2) = F(n) + F(n+1)`, starting from `(0, 1)`.
+    pub fn fibonacci() -> Self {
+        Self::new(vec![F::ONE, F::ONE], vec![F::ZERO, F::ONE])
+    }
+
+    /// `L(n+2) = L(n) + L(n+1)`, starting from `(2, 1)`.
+    pub fn lucas() -> Self {
+        Self::new(vec![F::ONE, F::ONE], vec![F::from(2), F::ONE])
+    }
+
+    /// `T(n+3) = T(n) + T(n+1) + T(n+2)`, starting from `(0, 0, 1)`.
+    pub fn tribonacci() -> Self {
+        Self::new(vec![F::ONE; 3], vec![F::ZERO, F::ZERO, F::ONE])
+    }
+
+    /// `P(n+2) = P(n) + 2 * P(n+1)`, starting from `(0, 1)`.
+    pub fn pell() -> Self {
+        Self::new(vec![F::ONE, F::from(2)], vec![F::ZERO, F::ONE])
+    }
+
+    pub fn arity(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    pub fn next_term(&self, terms: &[F]) -> F {
+        self.coefficients
+            .iter()
+            .zip(terms)
+            .fold(F::ZERO, |acc, (c, t)| acc + *c * t)
+    }
+
+    /// Applies `num_iters` iterations to `terms`.
+    pub fn advance(&self, terms: &[F], num_iters: usize) -> Vec<F> {
+        (0..num_iters).fold(terms.to_vec(), |mut terms, _| {
+            let next = self.next_term(&terms);
+            terms.remove(0);
+            terms.push(next);
+            terms
+        })
+    }
+
+    /// Window of terms reached after `num_iters` iterations from `initial`.
+    pub fn nth_terms(&self, num_iters: usize) -> Vec<F> {
+        self.advance(&self.initial, num_iters)
+    }
+
+    /// Native generator: returns `z0` and one object per iteration.
+    pub fn generate(&self, num_iters: usize) -> (Vec<F>, Vec<RecurrenceObject<F>>) {
         let res: Vec<_> = (0..num_iters)
-            .scan((*x_0, *y_0), |state, _| {
-                let (x, y) = *state;
-                let x_next = y;
-                let y_next = x + y;
-                *state = (x_next, y_next);
-                Some(Self {
-                    x,
-                    y,
-                    x_next,
-                    y_next,
-                })
+            .scan(self.initial.clone(), |terms, _| {
+                let next = self.next_term(terms);
+                let object = RecurrenceObject {
+                    terms: terms.clone(),
+                    next,
+                };
+                *terms = object.terms_next();
+                Some(object)
             })
             .collect();
 
-        (vec![*x_0, *y_0], res)
+        (self.initial.clone(), res)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecurrenceObject<F: PrimeField> {
+    pub terms: Vec<F>,
+    pub next: F,
+}
+
+impl<F: PrimeField> RecurrenceObject<F> {
+    pub fn terms_next(&self) -> Vec<F> {
+        let mut terms = self.terms[1..].to_vec();
+        terms.push(self.next);
+        terms
     }
 }
 
 #[derive(Clone, Debug)]
-pub struct FibonacciCircuit<F: PrimeField> {
-    pub values: Vec<FibonacciObject<F>>,
+pub struct LinearRecurrenceCircuit<F: PrimeField> {
+    pub coefficients: Vec<F>,
+    pub values: Vec<RecurrenceObject<F>>,
+}
+
+impl<F: PrimeField> LinearRecurrenceCircuit<F> {
+    /// Circuit with placeholder witnesses, used for public parameter setup.
+    pub fn setup(recurrence: &LinearRecurrence<F>, num_iters_per_step: usize) -> Self {
+        Self {
+            coefficients: recurrence.coefficients.clone(),
+            values: vec![
+                RecurrenceObject {
+                    terms: vec![F::ZERO; recurrence.arity()],
+                    next: F::ZERO,
+                };
+                num_iters_per_step
+            ],
+        }
+    }
 }
 
-impl<F: PrimeField> StepCircuit<F> for FibonacciCircuit<F> {
+impl<F: PrimeField> StepCircuit<F> for LinearRecurrenceCircuit<F> {
     fn arity(&self) -> usize {
-        2
+        self.coefficients.len()
     }
 
     fn synthesize<CS: ConstraintSystem<F>>(
@@ -49,63 +135,103 @@ impl<F: PrimeField> StepCircuit<F> for FibonacciCircuit<F> {
         cs: &mut CS,
         z: &[AllocatedNum<F>],
     ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
-        let mut z_out: Result<Vec<AllocatedNum<F>>, SynthesisError> =
-            Err(SynthesisError::AssignmentMissing);
-
-        let x_0 = z[0].clone();
-        let y_0 = z[1].clone();
+        let mut terms = z.to_vec();
 
-        let mut x = x_0;
-        let mut y = y_0;
-        for i in 0..self.values.len() {
-            let y_next = AllocatedNum::alloc(cs.namespace(|| format!("y_next_{i}")), || {
-                Ok(self.values[i].y_next)
-            })?;
-            let dummy = AllocatedNum::alloc(cs.namespace(|| format!("one_{i}")), || Ok(F::from(1)))?;
+        for (i, value) in self.values.iter().enumerate() {
+            let next =
+                AllocatedNum::alloc(cs.namespace(|| format!("next_{i}")), || Ok(value.next))?;
 
             cs.enforce(
-                || format!("y_next_{i} * 1 = x_{i} + y_{i}"),
-                |lc| lc + y_next.get_variable(),
-                |lc| lc + dummy.get_variable(),
-                |lc| lc + x.get_variable() + y.get_variable(),
+                || format!("next_{i} * 1 = sum c_j * term_{i}_j"),
+                |lc| lc + next.get_variable(),
+                |lc| lc + CS::one(),
+                |lc| {
+                    self.coefficients
+                        .iter()
+                        .zip(&terms)
+                        .fold(lc, |lc, (c, term)| lc + (*c, term.get_variable()))
+                },
             );
-            if i == self.values.len() - 1 {
-                z_out = Ok(vec![y.clone(), y_next.clone()]);
-            }
 
-            x = y;
-            y = y_next;
+            terms.remove(0);
+            terms.push(next);
         }
-        z_out
+
+        Ok(terms)
     }
 
+    /// Computes the step output from `z` rather than from the witnesses, so a
+    /// tampered witness shows up as an unsatisfied step instead of a forged `z_i`.
     fn output(&self, z: &[F]) -> Vec<F> {
-        debug_assert_eq!(z[0], self.values[0].x);
-        debug_assert_eq!(z[1], self.values[0].y);
-
-        vec![
-            self.values[self.values.len() - 1].x_next,
-            self.values[self.values.len() - 1].y_next,
-        ]
+        LinearRecurrence {
+            coefficients: self.coefficients.clone(),
+            initial: z.to_vec(),
+        }
+        .nth_terms(self.values.len())
     }
 }
 
+#[test]
+fn test_recurrence_presets() {
+    type F = pasta_curves::pallas::Scalar;
+
+    let first_terms = |recurrence: LinearRecurrence<F>, n: usize| {
+        let (z0, values) = recurrence.generate(n - recurrence.arity());
+        z0.into_iter()
+            .chain(values.iter().map(|v| v.next))
+            .collect::<Vec<_>>()
+    };
+    let expect = |terms: &[u64]| terms.iter().map(|t| F::from(*t)).collect::<Vec<_>>();
+
+    assert_eq!(
+        first_terms(LinearRecurrence::fibonacci(), 10),
+        expect(&[0, 1, 1, 2, 3, 5, 8, 13, 21, 34])
+    );
+    assert_eq!(
+        first_terms(LinearRecurrence::lucas(), 10),
+        expect(&[2, 1, 3, 4, 7, 11, 18, 29, 47, 76])
+    );
+    assert_eq!(
+        first_terms(LinearRecurrence::tribonacci(), 10),
+        expect(&[0, 0, 1, 1, 2, 4, 7, 13, 24, 44])
+    );
+    assert_eq!(
+        first_terms(LinearRecurrence::pell(), 10),
+        expect(&[0, 1, 2, 5, 12, 29, 70, 169, 408, 985])
+    );
+}
+
 #[test]
 fn test_basic() {
-    prove_and_verify(4, 10);
+    prove_and_verify(&LinearRecurrence::fibonacci(), 4, 10).unwrap();
 }
 
 #[test]
 fn test_medium() {
-    prove_and_verify(5, 50);
+    prove_and_verify(&LinearRecurrence::fibonacci(), 5, 50).unwrap();
 }
 
 #[test]
 fn test_bigger() {
-    prove_and_verify(4, 100);
+    prove_and_verify(&LinearRecurrence::fibonacci(), 4, 100).unwrap();
 }
 
 #[test]
 fn test_huge() {
-    prove_and_verify(10, 4096);
+    prove_and_verify(&LinearRecurrence::fibonacci(), 10, 4096).unwrap();
+}
+
+#[test]
+fn test_lucas() {
+    prove_and_verify(&LinearRecurrence::lucas(), 4, 10).unwrap();
+}
+
+#[test]
+fn test_tribonacci() {
+    prove_and_verify(&LinearRecurrence::tribonacci(), 4, 10).unwrap();
+}
+
+#[test]
+fn test_pell() {
+    prove_and_verify(&LinearRecurrence::pell(), 4, 10).unwrap();
 }
@@ -0,0 +1,271 @@
+use std::marker::PhantomData;
+
+use bellperson::{gadgets::num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError};
+use ff::PrimeField;
+use nova_snark::traits::circuit::StepCircuit;
+
+use crate::{
+    error::Error,
+    gadgets::decompose_u64,
+    u64_fibonacci::{field_to_u64, u64_to_field},
+};
+
+#[warn(unused_imports)]
+use crate::prove_verify::prove_and_verify_collatz;
+
+/// Native Collatz step, with 1 made absorbing so a sequence stays put once it
+/// has reached 1. Fails when `3n + 1` overflows, as for odd `n` above
+/// `(u64::MAX - 1) / 3`.
+pub fn collatz_next(n: u64) -> Result<u64, Error> {
+    if n == 1 {
+        Ok(1)
+    } else if n % 2 == 0 {
+        Ok(n / 2)
+    } else {
+        n.checked_mul(3)
+            .and_then(|n| n.checked_add(1))
+            .ok_or(Error::CollatzOverflow { n })
+    }
+}
+
+/// Number of Collatz steps `n` needs to reach 1, if it does within `max_steps`.
+pub fn stopping_time(mut n: u64, max_steps: u64) -> Result<Option<u64>, Error> {
+    for steps in 0..=max_steps {
+        if n == 1 {
+            return Ok(Some(steps));
+        }
+        n = collatz_next(n)?;
+    }
+    Ok(None)
+}
+
+/// One Collatz iteration on the state `(n, count)`, where `count` is the number
+/// of steps taken before reaching 1.
+#[derive(Clone, Debug)]
+pub struct CollatzObject {
+    pub n: u64,
+    pub count: u64,
+}
+
+impl CollatzObject {
+    /// Native generator: returns `z0 = (n_0, 0)` and one object per iteration.
+    pub fn new(num_iters: usize, n_0: u64) -> Result<(Vec<u64>, Vec<Self>), Error> {
+        let mut state = (n_0, 0);
+        let res = (0..num_iters)
+            .map(|_| {
+                let (n, count) = state;
+                state = Self::next(n, count)?;
+                Ok(Self { n, count })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok((vec![n_0, 0], res))
+    }
+
+    pub fn next(n: u64, count: u64) -> Result<(u64, u64), Error> {
+        if n == 1 {
+            Ok((1, count))
+        } else {
+            Ok((collatz_next(n)?, count + 1))
+        }
+    }
+}
+
+/// Collatz step circuit: `n` is decomposed into bits to read its parity and
+/// `n / 2`, and the next value is picked with conditional selection between
+/// `n / 2`, `3n + 1` and the absorbing 1.
+#[derive(Clone, Debug)]
+pub struct CollatzCircuit<F: PrimeField> {
+    pub values: Vec<CollatzObject>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> CollatzCircuit<F> {
+    pub fn new(values: Vec<CollatzObject>) -> Self {
+        Self {
+            values,
+            _f: PhantomData,
+        }
+    }
+
+    /// Circuit with placeholder witnesses, used for public parameter setup.
+    pub fn setup(num_iters_per_step: usize) -> Self {
+        Self::new(vec![CollatzObject { n: 0, count: 0 }; num_iters_per_step])
+    }
+}
+
+impl<F: PrimeField> StepCircuit<F> for CollatzCircuit<F> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let mut n = z[0].clone();
+        let mut count = z[1].clone();
+
+        for (i, value) in self.values.iter().enumerate() {
+            let (n_next_value, count_next_value) = CollatzObject::next(value.n, value.count)
+                .map_err(|_| SynthesisError::Unsatisfiable)?;
+
+            let bits = decompose_u64(cs.namespace(|| format!("bits_n_{i}")), &n, value.n)?;
+            let parity = &bits[0];
+
+            // is_one = (n == 1), via (n - 1) * inv = 1 - is_one and (n - 1) * is_one = 0
+            let n_minus_one = u64_to_field::<F>(value.n) - F::ONE;
+            let inv = AllocatedNum::alloc(cs.namespace(|| format!("inv_{i}")), || {
+                Ok(Option::from(n_minus_one.invert()).unwrap_or(F::ZERO))
+            })?;
+            let is_one = AllocatedNum::alloc(cs.namespace(|| format!("is_one_{i}")), || {
+                Ok(if value.n == 1 { F::ONE } else { F::ZERO })
+            })?;
+            cs.enforce(
+                || format!("(n_{i} - 1) * inv_{i} = 1 - is_one_{i}"),
+                |lc| lc + n.get_variable() - CS::one(),
+                |lc| lc + inv.get_variable(),
+                |lc| lc + CS::one() - is_one.get_variable(),
+            );
+            cs.enforce(
+                || format!("(n_{i} - 1) * is_one_{i} = 0"),
+                |lc| lc + n.get_variable() - CS::one(),
+                |lc| lc + is_one.get_variable(),
+                |lc| lc,
+            );
+
+            // step = parity ? 3n + 1 : n / 2, with n / 2 read off the high bits
+            let step = AllocatedNum::alloc(cs.namespace(|| format!("step_{i}")), || {
+                let step = if value.n % 2 == 0 {
+                    Some(value.n / 2)
+                } else {
+                    value.n.checked_mul(3).and_then(|n| n.checked_add(1))
+                };
+                step.map(u64_to_field).ok_or(SynthesisError::Unsatisfiable)
+            })?;
+            let minus_half = |lc: LinearCombination<F>| {
+                bits.iter().enumerate().skip(1).fold(lc, |lc, (j, bit)| {
+                    lc - (F::from(1u64 << (j - 1)), bit.get_variable())
+                })
+            };
+            cs.enforce(
+                || format!("parity_{i} * (3n_{i} + 1 - half_{i}) = step_{i} - half_{i}"),
+                |lc| lc + parity.get_variable(),
+                |lc| minus_half(lc + (F::from(3), n.get_variable()) + CS::one()),
+                |lc| minus_half(lc + step.get_variable()),
+            );
+
+            // n_next = is_one ? 1 : step
+            let n_next = AllocatedNum::alloc(cs.namespace(|| format!("n_next_{i}")), || {
+                Ok(u64_to_field(n_next_value))
+            })?;
+            cs.enforce(
+                || format!("is_one_{i} * (1 - step_{i}) = n_next_{i} - step_{i}"),
+                |lc| lc + is_one.get_variable(),
+                |lc| lc + CS::one() - step.get_variable(),
+                |lc| lc + n_next.get_variable() - step.get_variable(),
+            );
+
+            // count_next = count + 1 - is_one
+            let count_next =
+                AllocatedNum::alloc(cs.namespace(|| format!("count_next_{i}")), || {
+                    Ok(u64_to_field(count_next_value))
+                })?;
+            cs.enforce(
+                || format!("count_next_{i} * 1 = count_{i} + 1 - is_one_{i}"),
+                |lc| lc + count_next.get_variable(),
+                |lc| lc + CS::one(),
+                |lc| lc + count.get_variable() + CS::one() - is_one.get_variable(),
+            );
+
+            n = n_next;
+            count = count_next;
+        }
+
+        Ok(vec![n, count])
+    }
+
+    fn output(&self, z: &[F]) -> Vec<F> {
+        let (n, count) = (0..self.values.len())
+            .try_fold(
+                (field_to_u64(&z[0]), field_to_u64(&z[1])),
+                |(n, count), _| CollatzObject::next(n, count),
+            )
+            .expect("synthesize rejects states whose 3n + 1 overflows");
+
+        vec![u64_to_field(n), u64_to_field(count)]
+    }
+}
+
+#[test]
+fn test_collatz_native() {
+    assert_eq!(stopping_time(1, 0).unwrap(), Some(0));
+    assert_eq!(stopping_time(6, 100).unwrap(), Some(8));
+    assert_eq!(stopping_time(27, 200).unwrap(), Some(111));
+    assert_eq!(stopping_time(27, 100).unwrap(), None);
+
+    let (z0, values) = CollatzObject::new(128, 27).unwrap();
+    assert_eq!(z0, vec![27, 0]);
+    let last = values.last().unwrap();
+    assert_eq!(CollatzObject::next(last.n, last.count).unwrap(), (1, 111));
+}
+
+#[test]
+fn test_collatz_overflow() {
+    use crate::error::Error;
+
+    // the largest odd n with 3n + 1 in a u64, and the next one
+    assert_eq!(
+        collatz_next(6148914691236517203).unwrap(),
+        18446744073709551610
+    );
+    assert!(matches!(
+        collatz_next(6148914691236517205),
+        Err(Error::CollatzOverflow { .. })
+    ));
+    assert!(matches!(
+        prove_and_verify_collatz(u64::MAX, 1, 4),
+        Err(Error::CollatzOverflow { .. })
+    ));
+}
+
+#[test]
+fn test_collatz_reaches_one() {
+    use crate::prove_verify::S1;
+
+    // 27 needs 111 steps, 4 folds of 32 iterations cover them.
+    let zn_primary = prove_and_verify_collatz(27, 4, 32).unwrap();
+    assert_eq!(zn_primary, vec![S1::from(1), S1::from(111)]);
+}
+
+#[test]
+fn test_collatz_not_enough_steps() {
+    use crate::error::Error;
+
+    assert!(matches!(
+        prove_and_verify_collatz(27, 2, 32),
+        Err(Error::CollatzNotConverged { .. })
+    ));
+}
+
+#[test]
+fn test_collatz_tampered_count() {
+    use crate::{error::Error, prove_verify::prove_and_verify_collatz_circuits};
+
+    let (z0, mut values) = CollatzObject::new(16, 6).unwrap();
+    // Claim the sequence reached 1 two steps earlier than it did.
+    for value in values.iter_mut().skip(4) {
+        value.count = value.count.saturating_sub(2);
+    }
+
+    let circuits = values
+        .chunks(8)
+        .map(|values| CollatzCircuit::new(values.to_vec()))
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        prove_and_verify_collatz_circuits(&z0, circuits),
+        Err(Error::Nova(_))
+    ));
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use nova_snark::errors::NovaError;
+
+use crate::prove_verify::S1;
+
+#[derive(Debug)]
+pub enum Error {
+    Nova(NovaError),
+    InvalidParameters(String),
+    OutputMismatch { expected: Vec<S1>, actual: Vec<S1> },
+    CollatzNotConverged { start: u64, num_iters: usize },
+    CollatzOverflow { n: u64 },
+    FastDoublingTooSmall { n: u128 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Nova(e) => write!(f, "nova error: {e:?}"),
+            Error::InvalidParameters(msg) => write!(f, "invalid parameters: {msg}"),
+            Error::OutputMismatch { expected, actual } => write!(
+                f,
+                "verified output {actual:?} does not match the expected {expected:?}"
+            ),
+            Error::CollatzNotConverged { start, num_iters } => write!(
+                f,
+                "Collatz sequence from {start} does not reach 1 within {num_iters} steps"
+            ),
+            Error::CollatzOverflow { n } => {
+                write!(f, "Collatz step 3 * {n} + 1 overflows u64")
+            }
+            Error::FastDoublingTooSmall { n } => {
+                write!(
+                    f,
+                    "fast doubling needs at least one fold, n ({n}) must be at least 2"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
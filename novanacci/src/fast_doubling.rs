@@ -0,0 +1,195 @@
+use bellperson::{gadgets::num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_snark::traits::circuit::StepCircuit;
+
+#[warn(unused_imports)]
+use crate::{
+    circuit::LinearRecurrence, error::Error, prove_verify::prove_and_verify_fast_doubling,
+};
+
+/// One fast-doubling fold: `(x, y) = (F(k), F(k+1))` moves to
+/// `(F(2k), F(2k+1))` when `bit` is unset and to `(F(2k+1), F(2k+2))` otherwise.
+#[derive(Clone, Debug)]
+pub struct FastDoublingObject<F: PrimeField> {
+    pub bit: bool,
+    pub x: F,
+    pub y: F,
+    pub x_next: F,
+    pub y_next: F,
+}
+
+impl<F: PrimeField> FastDoublingObject<F> {
+    /// Returns `z0` and one object per bit of `n` below the leading one.
+    ///
+    /// The leading bit is folded into the initial state `(F(1), F(2))`, so
+    /// `F(2^64)` takes 64 folds.
+    pub fn new(n: u128) -> (Vec<F>, Vec<Self>) {
+        if n == 0 {
+            return (vec![F::ZERO, F::ONE], vec![]);
+        }
+
+        let num_bits = 128 - n.leading_zeros() as usize;
+        let z0 = vec![F::ONE, F::ONE];
+
+        let res: Vec<_> = (0..num_bits - 1)
+            .rev()
+            .scan((z0[0], z0[1]), |state, i| {
+                let (x, y) = *state;
+                let bit = (n >> i) & 1 == 1;
+                let (x_next, y_next) = Self::double(x, y, bit);
+                *state = (x_next, y_next);
+                Some(Self {
+                    bit,
+                    x,
+                    y,
+                    x_next,
+                    y_next,
+                })
+            })
+            .collect();
+
+        (z0, res)
+    }
+
+    fn double(x: F, y: F, bit: bool) -> (F, F) {
+        let c = x * (y.double() - x);
+        let d = x.square() + y.square();
+        if bit {
+            (d, c + d)
+        } else {
+            (c, d)
+        }
+    }
+}
+
+/// Native fast-doubling evaluator returning `(F(n), F(n+1))`.
+pub fn fibonacci_fast_doubling<F: PrimeField>(n: u128) -> (F, F) {
+    (0..128 - n.leading_zeros() as usize)
+        .rev()
+        .fold((F::ZERO, F::ONE), |(x, y), i| {
+            FastDoublingObject::double(x, y, (n >> i) & 1 == 1)
+        })
+}
+
+#[derive(Clone, Debug)]
+pub struct FastDoublingCircuit<F: PrimeField> {
+    pub value: FastDoublingObject<F>,
+}
+
+impl<F: PrimeField> StepCircuit<F> for FastDoublingCircuit<F> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let x = z[0].clone();
+        let y = z[1].clone();
+
+        let value = &self.value;
+        let c_value = value.x * (value.y.double() - value.x);
+        let d_value = value.x.square() + value.y.square();
+
+        let bit = AllocatedNum::alloc(cs.namespace(|| "bit"), || {
+            Ok(if value.bit { F::ONE } else { F::ZERO })
+        })?;
+        cs.enforce(
+            || "bit * (1 - bit) = 0",
+            |lc| lc + bit.get_variable(),
+            |lc| lc + CS::one() - bit.get_variable(),
+            |lc| lc,
+        );
+
+        // c = F(2k) = x * (2y - x)
+        let c = AllocatedNum::alloc(cs.namespace(|| "c"), || Ok(c_value))?;
+        cs.enforce(
+            || "x * (2y - x) = c",
+            |lc| lc + x.get_variable(),
+            |lc| lc + y.get_variable() + y.get_variable() - x.get_variable(),
+            |lc| lc + c.get_variable(),
+        );
+
+        // d = F(2k+1) = x^2 + y^2
+        let x_sq = x.square(cs.namespace(|| "x^2"))?;
+        let d = AllocatedNum::alloc(cs.namespace(|| "d"), || Ok(d_value))?;
+        cs.enforce(
+            || "y * y = d - x^2",
+            |lc| lc + y.get_variable(),
+            |lc| lc + y.get_variable(),
+            |lc| lc + d.get_variable() - x_sq.get_variable(),
+        );
+
+        // (x_next, y_next) = bit ? (d, c + d) : (c, d)
+        let x_next = AllocatedNum::alloc(cs.namespace(|| "x_next"), || Ok(value.x_next))?;
+        cs.enforce(
+            || "bit * (d - c) = x_next - c",
+            |lc| lc + bit.get_variable(),
+            |lc| lc + d.get_variable() - c.get_variable(),
+            |lc| lc + x_next.get_variable() - c.get_variable(),
+        );
+
+        let y_next = AllocatedNum::alloc(cs.namespace(|| "y_next"), || Ok(value.y_next))?;
+        cs.enforce(
+            || "bit * c = y_next - d",
+            |lc| lc + bit.get_variable(),
+            |lc| lc + c.get_variable(),
+            |lc| lc + y_next.get_variable() - d.get_variable(),
+        );
+
+        Ok(vec![x_next, y_next])
+    }
+
+    fn output(&self, z: &[F]) -> Vec<F> {
+        let (x_next, y_next) = FastDoublingObject::double(z[0], z[1], self.value.bit);
+        vec![x_next, y_next]
+    }
+}
+
+#[test]
+fn test_fast_doubling_native() {
+    type F = pasta_curves::pallas::Scalar;
+
+    let (_, fibo_iterations) = LinearRecurrence::<F>::fibonacci().generate(200);
+    for n in 1..=200 {
+        let (f_n, f_n_plus_1) = fibonacci_fast_doubling::<F>(n as u128);
+        assert_eq!(vec![f_n, f_n_plus_1], fibo_iterations[n - 1].terms_next());
+    }
+    assert_eq!(fibonacci_fast_doubling::<F>(0), (F::ZERO, F::ONE));
+}
+
+#[test]
+fn test_fast_doubling_witness() {
+    type F = pasta_curves::pallas::Scalar;
+
+    for n in 2..=200u128 {
+        let (z0, values) = FastDoublingObject::<F>::new(n);
+        assert_eq!(values.len(), 127 - n.leading_zeros() as usize);
+        assert_eq!((values[0].x, values[0].y), (z0[0], z0[1]));
+
+        let last = values.last().unwrap();
+        assert_eq!((last.x_next, last.y_next), fibonacci_fast_doubling::<F>(n));
+    }
+}
+
+#[test]
+fn test_fast_doubling_basic() {
+    prove_and_verify_fast_doubling(100).unwrap();
+}
+
+#[test]
+fn test_fast_doubling_2_pow_64() {
+    prove_and_verify_fast_doubling(1 << 64).unwrap();
+}
+
+#[test]
+fn test_fast_doubling_too_small() {
+    for n in 0..2 {
+        assert!(matches!(
+            prove_and_verify_fast_doubling(n),
+            Err(Error::FastDoublingTooSmall { .. })
+        ));
+    }
+}
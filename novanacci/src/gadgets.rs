@@ -0,0 +1,39 @@
+use bellperson::{
+    gadgets::{boolean::AllocatedBit, num::AllocatedNum},
+    ConstraintSystem, SynthesisError,
+};
+use ff::PrimeField;
+
+/// Decomposes `num` into 64 little-endian bits of `value`, which range-checks
+/// it to `[0, 2^64)`.
+pub fn decompose_u64<F, CS>(
+    mut cs: CS,
+    num: &AllocatedNum<F>,
+    value: u64,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let bits = (0..64)
+        .map(|i| {
+            AllocatedBit::alloc(
+                cs.namespace(|| format!("bit_{i}")),
+                Some((value >> i) & 1 == 1),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cs.enforce(
+        || "sum bit_i * 2^i = num",
+        |lc| {
+            bits.iter().enumerate().fold(lc, |lc, (i, bit)| {
+                lc + (F::from(1u64 << i), bit.get_variable())
+            })
+        },
+        |lc| lc + CS::one(),
+        |lc| lc + num.get_variable(),
+    );
+
+    Ok(bits)
+}
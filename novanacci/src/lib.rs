@@ -0,0 +1,8 @@
+pub mod artifacts;
+pub mod circuit;
+pub mod collatz;
+pub mod error;
+pub mod fast_doubling;
+pub mod gadgets;
+pub mod prove_verify;
+pub mod u64_fibonacci;
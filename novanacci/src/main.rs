@@ -1,8 +1,153 @@
-use prove_verify::prove_and_verify;
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
-mod circuit;
-mod prove_verify;
+use clap::{Parser, Subcommand};
+use ff::PrimeField;
+use novanacci::{
+    artifacts::{load_or_setup_public_params, DEFAULT_PARAMS_DIR},
+    circuit::LinearRecurrence,
+    prove_verify::{prove, verify, RecurrenceProof, RecurrencePublicParams, S1},
+};
 
-fn main() {
-    prove_and_verify(5, 64);
+#[derive(Parser)]
+#[command(about = "Prove and verify Fibonacci numbers with Nova")]
+struct Cli {
+    /// Directory where public parameters are cached
+    #[arg(long, global = true, default_value = DEFAULT_PARAMS_DIR)]
+    params_dir: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prove F(n), folding `iters-per-step` Fibonacci iterations per step
+    Prove {
+        /// Fibonacci index, must be a multiple of `iters-per-step`
+        #[arg(long)]
+        n: usize,
+        #[arg(long, default_value_t = 64)]
+        iters_per_step: usize,
+        #[arg(long, default_value = "proof.bin")]
+        out: PathBuf,
+    },
+    /// Verify a proof and check that it attests to `claim` = F(n), n being the number of
+    /// iterations the proof folds
+    Verify {
+        proof: PathBuf,
+        /// Claimed F(n), decimal or 0x-prefixed big-endian hex
+        #[arg(long)]
+        claim: String,
+    },
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Prove {
+            n,
+            iters_per_step,
+            out,
+        } => {
+            if iters_per_step == 0 || n == 0 || n % iters_per_step != 0 {
+                return Err(format!(
+                    "n ({n}) must be a positive multiple of iters-per-step ({iters_per_step})"
+                )
+                .into());
+            }
+            let num_steps = n / iters_per_step;
+
+            let pp = public_params(&cli.params_dir, iters_per_step)?;
+
+            let start = Instant::now();
+            let proof = prove(
+                &pp,
+                &LinearRecurrence::fibonacci(),
+                num_steps,
+                iters_per_step,
+            )?;
+            println!("Proving {num_steps} steps took {:?}", start.elapsed());
+
+            proof.write(&out)?;
+            println!(
+                "Proof written to {} ({} bytes)",
+                out.display(),
+                fs::metadata(&out)?.len()
+            );
+
+            let f_n = LinearRecurrence::<S1>::fibonacci().nth_terms(n)[0];
+            println!("F({n}) = {f_n:?}");
+        }
+        Command::Verify { proof, claim } => {
+            let claim = parse_field_element(&claim)
+                .ok_or_else(|| format!("invalid field element: {claim}"))?;
+
+            println!("Proof size: {} bytes", fs::metadata(&proof)?.len());
+            let proof = RecurrenceProof::read(&proof)?;
+            let n = proof.num_steps * proof.num_iters_per_step;
+
+            let pp = public_params(&cli.params_dir, proof.num_iters_per_step)?;
+
+            let start = Instant::now();
+            let zn_primary = verify(&pp, &LinearRecurrence::fibonacci(), &proof)?;
+            println!("Verification took {:?}", start.elapsed());
+
+            if zn_primary[0] != claim {
+                return Err(format!(
+                    "proof attests to F({n}) = {:?}, not the claimed {claim:?}",
+                    zn_primary[0]
+                )
+                .into());
+            }
+            println!("Verified F({n}) = {claim:?}");
+        }
+    }
+
+    Ok(())
+}
+
+fn public_params(
+    params_dir: &Path,
+    iters_per_step: usize,
+) -> Result<RecurrencePublicParams, Box<dyn Error>> {
+    let start = Instant::now();
+    let (pp, cached) = load_or_setup_public_params(params_dir, iters_per_step)?;
+    println!(
+        "{} public params for {iters_per_step} iterations per step in {:?}",
+        if cached { "Loaded" } else { "Generated" },
+        start.elapsed()
+    );
+    println!(
+        "Number of constraints per step (primary circuit): {}",
+        pp.num_constraints().0
+    );
+    println!(
+        "Number of constraints per step (secondary circuit): {}",
+        pp.num_constraints().1
+    );
+
+    Ok(pp)
+}
+
+fn parse_field_element(value: &str) -> Option<S1> {
+    let Some(hex) = value.strip_prefix("0x") else {
+        return S1::from_str_vartime(value);
+    };
+
+    let mut repr = <S1 as PrimeField>::Repr::default();
+    let hex = format!("{hex:0>64}");
+    if hex.len() != 2 * repr.as_ref().len() {
+        return None;
+    }
+    for (byte, chunk) in repr.as_mut().iter_mut().rev().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+
+    Option::from(S1::from_repr(repr))
 }
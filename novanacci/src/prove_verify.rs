@@ -1,74 +1,419 @@
-use crate::circuit::{FibonacciCircuit, FibonacciObject};
+use crate::circuit::{LinearRecurrence, LinearRecurrenceCircuit};
+use crate::collatz::{CollatzCircuit, CollatzObject};
+use crate::error::Error;
+use crate::fast_doubling::{fibonacci_fast_doubling, FastDoublingCircuit, FastDoublingObject};
+use crate::u64_fibonacci::{u64_to_field, U64FibonacciCircuit, U64FibonacciObject};
 use nova_snark::{
-    traits::{circuit::TrivialTestCircuit, Group},
+    traits::{
+        circuit::{StepCircuit, TrivialTestCircuit},
+        Group,
+    },
     PublicParams, RecursiveSNARK,
 };
+use serde::{Deserialize, Serialize};
 
-type G1 = pasta_curves::pallas::Point;
-type G2 = pasta_curves::vesta::Point;
-
-pub fn prove_and_verify(num_steps: usize, num_iters_per_step: usize) {
-    let circuit_primary = FibonacciCircuit {
-        values: vec![
-            FibonacciObject {
-                x: <G1 as Group>::Scalar::zero(),
-                y: <G1 as Group>::Scalar::zero(),
-                x_next: <G1 as Group>::Scalar::zero(),
-                y_next: <G1 as Group>::Scalar::zero(),
-            };
-            num_iters_per_step
-        ],
-    };
+pub type G1 = pasta_curves::pallas::Point;
+pub type G2 = pasta_curves::vesta::Point;
+
+pub type S1 = <G1 as Group>::Scalar;
+pub type S2 = <G2 as Group>::Scalar;
+
+pub type C1 = LinearRecurrenceCircuit<S1>;
+pub type C2 = TrivialTestCircuit<S2>;
+
+pub type RecurrencePublicParams = PublicParams<G1, G2, C1, C2>;
+pub type RecurrenceSNARK = RecursiveSNARK<G1, G2, C1, C2>;
 
+pub fn public_params(
+    recurrence: &LinearRecurrence<S1>,
+    num_iters_per_step: usize,
+) -> RecurrencePublicParams {
+    let circuit_primary = LinearRecurrenceCircuit::setup(recurrence, num_iters_per_step);
     let circuit_secondary = TrivialTestCircuit::default();
 
-    let pp = PublicParams::<
-        G1,
-        G2,
-        FibonacciCircuit<<G1 as Group>::Scalar>,
-        TrivialTestCircuit<<G2 as Group>::Scalar>,
-    >::setup(circuit_primary, circuit_secondary.clone());
-
-    let (z0_primary, fibo_iterations) = FibonacciObject::new(
-        num_iters_per_step * num_steps,
-        &<G1 as Group>::Scalar::zero(),
-        &<G1 as Group>::Scalar::one(),
-    );
-    let fibo_circuits = (0..num_steps)
-        .map(|i| FibonacciCircuit {
-            values: (0..num_iters_per_step)
-                .map(|j| FibonacciObject {
-                    x: fibo_iterations[i * num_iters_per_step + j].x,
-                    y: fibo_iterations[i * num_iters_per_step + j].y,
-                    x_next: fibo_iterations[i * num_iters_per_step + j].x_next,
-                    y_next: fibo_iterations[i * num_iters_per_step + j].y_next,
-                })
-                .collect::<Vec<_>>(),
+    RecurrencePublicParams::setup(circuit_primary, circuit_secondary)
+}
+
+pub fn z0_secondary() -> Vec<S2> {
+    vec![S2::zero()]
+}
+
+/// Folded recurrence proof together with the shape needed to verify it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecurrenceProof {
+    pub num_steps: usize,
+    pub num_iters_per_step: usize,
+    pub snark: RecurrenceSNARK,
+}
+
+/// Splits `num_steps * num_iters_per_step` natively generated iterations into
+/// step circuits, returning `z0_primary` and the circuits.
+pub fn make_circuits(
+    recurrence: &LinearRecurrence<S1>,
+    num_steps: usize,
+    num_iters_per_step: usize,
+) -> (Vec<S1>, Vec<C1>) {
+    let (z0_primary, iterations) = recurrence.generate(num_iters_per_step * num_steps);
+    let recurrence_circuits = iterations
+        .chunks(num_iters_per_step)
+        .map(|values| LinearRecurrenceCircuit {
+            coefficients: recurrence.coefficients.clone(),
+            values: values.to_vec(),
         })
         .collect::<Vec<_>>();
 
-    let z0_secondary = vec![<G2 as Group>::Scalar::zero()];
-
-    type C1 = FibonacciCircuit<<G1 as Group>::Scalar>;
-    type C2 = TrivialTestCircuit<<G2 as Group>::Scalar>;
-    let mut recursive_snark: Option<RecursiveSNARK<G1, G2, C1, C2>> = None;
-    for circuit_primary in fibo_circuits.iter().take(num_steps) {
-        let res = RecursiveSNARK::prove_step(
-            &pp,
-            recursive_snark,
-            circuit_primary.clone(),
-            circuit_secondary.clone(),
-            z0_primary.clone(),
-            z0_secondary.clone(),
+    (z0_primary, recurrence_circuits)
+}
+
+/// Folds any primary step circuits from `z0_primary` with the trivial
+/// secondary circuit.
+fn fold_circuits<C>(
+    pp: &PublicParams<G1, G2, C, C2>,
+    z0_primary: Vec<S1>,
+    circuits: &[C],
+) -> Result<RecursiveSNARK<G1, G2, C, C2>, Error>
+where
+    C: StepCircuit<S1>,
+{
+    if circuits.is_empty() {
+        return Err(Error::InvalidParameters("no step circuits to fold".into()));
+    }
+
+    let circuit_secondary = TrivialTestCircuit::default();
+    let z0_secondary = z0_secondary();
+
+    let mut recursive_snark: Option<RecursiveSNARK<G1, G2, C, C2>> = None;
+    for circuit_primary in circuits {
+        recursive_snark = Some(
+            RecursiveSNARK::prove_step(
+                pp,
+                recursive_snark,
+                circuit_primary.clone(),
+                circuit_secondary.clone(),
+                z0_primary.clone(),
+                z0_secondary.clone(),
+            )
+            .map_err(Error::Nova)?,
         );
-        assert!(res.is_ok());
-        recursive_snark = Some(res.unwrap());
     }
 
-    assert!(recursive_snark.is_some());
-    let recursive_snark = recursive_snark.unwrap();
+    Ok(recursive_snark.unwrap())
+}
+
+/// Verifies `num_steps` folded steps from `z0_primary`, returning `z_n`.
+fn verify_folded<C>(
+    pp: &PublicParams<G1, G2, C, C2>,
+    snark: &RecursiveSNARK<G1, G2, C, C2>,
+    num_steps: usize,
+    z0_primary: Vec<S1>,
+) -> Result<Vec<S1>, Error>
+where
+    C: StepCircuit<S1>,
+{
+    let (zn_primary, _) = snark
+        .verify(pp, num_steps, z0_primary, z0_secondary())
+        .map_err(Error::Nova)?;
+
+    Ok(zn_primary)
+}
+
+/// Folds the given step circuits starting from `z0_primary`.
+pub fn prove_circuits(
+    pp: &RecurrencePublicParams,
+    z0_primary: Vec<S1>,
+    circuits: &[C1],
+) -> Result<RecurrenceProof, Error> {
+    let snark = fold_circuits(pp, z0_primary, circuits)?;
+
+    Ok(RecurrenceProof {
+        num_steps: circuits.len(),
+        num_iters_per_step: circuits[0].values.len(),
+        snark,
+    })
+}
+
+/// Folds `num_steps` steps of `num_iters_per_step` recurrence iterations each.
+pub fn prove(
+    pp: &RecurrencePublicParams,
+    recurrence: &LinearRecurrence<S1>,
+    num_steps: usize,
+    num_iters_per_step: usize,
+) -> Result<RecurrenceProof, Error> {
+    if num_steps == 0 || num_iters_per_step == 0 {
+        return Err(Error::InvalidParameters(format!(
+            "num_steps ({num_steps}) and num_iters_per_step ({num_iters_per_step}) must be positive"
+        )));
+    }
+
+    let (z0_primary, circuits) = make_circuits(recurrence, num_steps, num_iters_per_step);
+
+    prove_circuits(pp, z0_primary, &circuits)
+}
+
+/// Verifies `proof` from the recurrence's initial terms and checks the final
+/// terms against the natively computed ones, returning the verified terms.
+pub fn verify(
+    pp: &RecurrencePublicParams,
+    recurrence: &LinearRecurrence<S1>,
+    proof: &RecurrenceProof,
+) -> Result<Vec<S1>, Error> {
+    let zn_primary = verify_folded(
+        pp,
+        &proof.snark,
+        proof.num_steps,
+        recurrence.initial.clone(),
+    )?;
+
+    let expected = recurrence.nth_terms(proof.num_steps * proof.num_iters_per_step);
+    if zn_primary != expected {
+        return Err(Error::OutputMismatch {
+            expected,
+            actual: zn_primary,
+        });
+    }
+
+    Ok(zn_primary)
+}
+
+pub fn prove_and_verify(
+    recurrence: &LinearRecurrence<S1>,
+    num_steps: usize,
+    num_iters_per_step: usize,
+) -> Result<(RecurrenceProof, Vec<S1>), Error> {
+    let pp = public_params(recurrence, num_iters_per_step);
+
+    let proof = prove(&pp, recurrence, num_steps, num_iters_per_step)?;
+    let zn_primary = verify(&pp, recurrence, &proof)?;
+
+    Ok((proof, zn_primary))
+}
+
+pub fn prove_and_verify_fast_doubling(n: u128) -> Result<Vec<S1>, Error> {
+    if n < 2 {
+        return Err(Error::FastDoublingTooSmall { n });
+    }
+    let (z0_primary, fast_doubling_iterations) =
+        FastDoublingObject::<<G1 as Group>::Scalar>::new(n);
+
+    let circuit_primary = FastDoublingCircuit {
+        value: FastDoublingObject {
+            bit: false,
+            x: <G1 as Group>::Scalar::zero(),
+            y: <G1 as Group>::Scalar::zero(),
+            x_next: <G1 as Group>::Scalar::zero(),
+            y_next: <G1 as Group>::Scalar::zero(),
+        },
+    };
+
+    let pp = PublicParams::<G1, G2, FastDoublingCircuit<S1>, C2>::setup(
+        circuit_primary,
+        TrivialTestCircuit::default(),
+    );
+
+    let fast_doubling_circuits = fast_doubling_iterations
+        .into_iter()
+        .map(|value| FastDoublingCircuit { value })
+        .collect::<Vec<_>>();
+
+    let zn_primary = fold_and_verify(&pp, z0_primary, fast_doubling_circuits)?;
+
+    let (f_n, f_n_plus_1) = fibonacci_fast_doubling(n);
+    let expected = vec![f_n, f_n_plus_1];
+    if zn_primary != expected {
+        return Err(Error::OutputMismatch {
+            expected,
+            actual: zn_primary,
+        });
+    }
+
+    Ok(zn_primary)
+}
+
+/// Folds `circuits` from `z0_primary` with the trivial secondary circuit and
+/// returns the verified `z_n`.
+pub fn fold_and_verify<C>(
+    pp: &PublicParams<G1, G2, C, C2>,
+    z0_primary: Vec<S1>,
+    circuits: Vec<C>,
+) -> Result<Vec<S1>, Error>
+where
+    C: StepCircuit<S1>,
+{
+    let snark = fold_circuits(pp, z0_primary.clone(), &circuits)?;
+    verify_folded(pp, &snark, circuits.len(), z0_primary)
+}
+
+/// Folds and verifies `u64` Fibonacci steps from `z0`, checking the verified
+/// state against the native `u64` reference.
+pub fn prove_and_verify_u64_circuits(
+    z0: &[u64],
+    circuits: Vec<U64FibonacciCircuit<S1>>,
+) -> Result<Vec<S1>, Error> {
+    let num_iters_per_step = match circuits.first() {
+        Some(circuit) => circuit.values.len(),
+        None => return Err(Error::InvalidParameters("no step circuits to fold".into())),
+    };
+    let num_iters = num_iters_per_step * circuits.len();
+
+    let pp = PublicParams::<G1, G2, U64FibonacciCircuit<S1>, C2>::setup(
+        U64FibonacciCircuit::setup(num_iters_per_step),
+        TrivialTestCircuit::default(),
+    );
+
+    let z0_primary = z0.iter().map(|v| u64_to_field(*v)).collect();
+    let zn_primary = fold_and_verify(&pp, z0_primary, circuits)?;
+
+    let (_, reference) = U64FibonacciObject::new(num_iters, z0[0], z0[1]);
+    let last = &reference[num_iters - 1];
+    let expected = vec![u64_to_field(last.y), u64_to_field(last.y_next)];
+    if zn_primary != expected {
+        return Err(Error::OutputMismatch {
+            expected,
+            actual: zn_primary,
+        });
+    }
+
+    Ok(zn_primary)
+}
+
+pub fn prove_and_verify_u64(num_steps: usize, num_iters_per_step: usize) -> Result<Vec<S1>, Error> {
+    if num_steps == 0 || num_iters_per_step == 0 {
+        return Err(Error::InvalidParameters(format!(
+            "num_steps ({num_steps}) and num_iters_per_step ({num_iters_per_step}) must be positive"
+        )));
+    }
+
+    let (z0, values) = U64FibonacciObject::new(num_steps * num_iters_per_step, 0, 1);
+    let circuits = values
+        .chunks(num_iters_per_step)
+        .map(|values| U64FibonacciCircuit::new(values.to_vec()))
+        .collect();
+
+    prove_and_verify_u64_circuits(&z0, circuits)
+}
+
+/// Folds and verifies Collatz steps from `z0`, checking the verified state
+/// against the native reference and that the sequence reached 1.
+pub fn prove_and_verify_collatz_circuits(
+    z0: &[u64],
+    circuits: Vec<CollatzCircuit<S1>>,
+) -> Result<Vec<S1>, Error> {
+    let num_iters_per_step = match circuits.first() {
+        Some(circuit) => circuit.values.len(),
+        None => return Err(Error::InvalidParameters("no step circuits to fold".into())),
+    };
+    let num_iters = num_iters_per_step * circuits.len();
+
+    let pp = PublicParams::<G1, G2, CollatzCircuit<S1>, C2>::setup(
+        CollatzCircuit::setup(num_iters_per_step),
+        TrivialTestCircuit::default(),
+    );
+
+    let z0_primary = z0.iter().map(|v| u64_to_field(*v)).collect();
+    let zn_primary = fold_and_verify(&pp, z0_primary, circuits)?;
+
+    let (n, count) = (0..num_iters).try_fold((z0[0], z0[1]), |(n, count), _| {
+        CollatzObject::next(n, count)
+    })?;
+    let expected = vec![u64_to_field(n), u64_to_field(count)];
+    if zn_primary != expected {
+        return Err(Error::OutputMismatch {
+            expected,
+            actual: zn_primary,
+        });
+    }
+    if n != 1 {
+        return Err(Error::CollatzNotConverged {
+            start: z0[0],
+            num_iters,
+        });
+    }
 
-    recursive_snark
-        .verify(&pp, num_steps, z0_primary.clone(), z0_secondary.clone())
-        .unwrap();
+    Ok(zn_primary)
+}
+
+/// Proves that `start` reaches 1 within `num_steps * num_iters_per_step`
+/// Collatz steps, returning the verified `(1, stopping time)`.
+pub fn prove_and_verify_collatz(
+    start: u64,
+    num_steps: usize,
+    num_iters_per_step: usize,
+) -> Result<Vec<S1>, Error> {
+    if num_steps == 0 || num_iters_per_step == 0 {
+        return Err(Error::InvalidParameters(format!(
+            "num_steps ({num_steps}) and num_iters_per_step ({num_iters_per_step}) must be positive"
+        )));
+    }
+
+    let (z0, values) = CollatzObject::new(num_steps * num_iters_per_step, start)?;
+    let circuits = values
+        .chunks(num_iters_per_step)
+        .map(|values| CollatzCircuit::new(values.to_vec()))
+        .collect();
+
+    prove_and_verify_collatz_circuits(&z0, circuits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verified_output() {
+        let (_, zn_primary) = prove_and_verify(&LinearRecurrence::fibonacci(), 4, 10).unwrap();
+
+        // F(40), F(41)
+        assert_eq!(zn_primary, vec![S1::from(102334155), S1::from(165580141)]);
+    }
+
+    #[test]
+    fn test_tampered_witness() {
+        let fibonacci = LinearRecurrence::fibonacci();
+        let pp = public_params(&fibonacci, 10);
+
+        let (z0_primary, mut circuits) = make_circuits(&fibonacci, 4, 10);
+        circuits[2].values[5].next += S1::one();
+
+        let res = prove_circuits(&pp, z0_primary, &circuits)
+            .and_then(|proof| verify(&pp, &fibonacci, &proof));
+        assert!(matches!(res, Err(Error::Nova(_))));
+    }
+
+    #[test]
+    fn test_tampered_initial_state() {
+        let fibonacci = LinearRecurrence::fibonacci();
+        let pp = public_params(&fibonacci, 10);
+
+        // Valid Fibonacci steps, but started from the Lucas initial terms.
+        let (z0_primary, circuits) = make_circuits(&LinearRecurrence::lucas(), 4, 10);
+
+        let res = prove_circuits(&pp, z0_primary, &circuits)
+            .and_then(|proof| verify(&pp, &fibonacci, &proof));
+        assert!(matches!(res, Err(Error::Nova(_))));
+    }
+
+    #[test]
+    fn test_tampered_proof_shape() {
+        let fibonacci = LinearRecurrence::fibonacci();
+        let pp = public_params(&fibonacci, 10);
+
+        let mut proof = prove(&pp, &fibonacci, 4, 10).unwrap();
+        proof.num_iters_per_step = 9;
+
+        assert!(matches!(
+            verify(&pp, &fibonacci, &proof),
+            Err(Error::OutputMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let fibonacci = LinearRecurrence::fibonacci();
+        let pp = public_params(&fibonacci, 10);
+
+        assert!(matches!(
+            prove(&pp, &fibonacci, 0, 10),
+            Err(Error::InvalidParameters(_))
+        ));
+    }
 }
@@ -0,0 +1,203 @@
+use std::marker::PhantomData;
+
+use bellperson::{
+    gadgets::{boolean::AllocatedBit, num::AllocatedNum},
+    ConstraintSystem, SynthesisError,
+};
+use ff::PrimeField;
+use nova_snark::traits::circuit::StepCircuit;
+
+use crate::gadgets::decompose_u64;
+
+#[warn(unused_imports)]
+use crate::prove_verify::prove_and_verify_u64;
+
+/// One Fibonacci iteration over `u64` machine words:
+/// `(x, y)` moves to `(y, y_next)` with `y_next + carry * 2^64 = x + y`.
+#[derive(Clone, Debug)]
+pub struct U64FibonacciObject {
+    pub x: u64,
+    pub y: u64,
+    pub y_next: u64,
+    pub carry: bool,
+}
+
+impl U64FibonacciObject {
+    /// Native `u64` reference: returns `z0` and one object per iteration.
+    pub fn new(num_iters: usize, x_0: u64, y_0: u64) -> (Vec<u64>, Vec<Self>) {
+        let res: Vec<_> = (0..num_iters)
+            .scan((x_0, y_0), |state, _| {
+                let (x, y) = *state;
+                let (y_next, carry) = x.overflowing_add(y);
+                *state = (y, y_next);
+                Some(Self {
+                    x,
+                    y,
+                    y_next,
+                    carry,
+                })
+            })
+            .collect();
+
+        (vec![x_0, y_0], res)
+    }
+}
+
+/// Fibonacci step circuit with `u64` wraparound semantics.
+///
+/// Both state values are range-checked to 64 bits when entering the step, and
+/// every new value is range-checked as it is produced, so the state stays a
+/// pair of machine words across folds.
+#[derive(Clone, Debug)]
+pub struct U64FibonacciCircuit<F: PrimeField> {
+    pub values: Vec<U64FibonacciObject>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> U64FibonacciCircuit<F> {
+    pub fn new(values: Vec<U64FibonacciObject>) -> Self {
+        Self {
+            values,
+            _f: PhantomData,
+        }
+    }
+
+    /// Circuit with placeholder witnesses, used for public parameter setup.
+    pub fn setup(num_iters_per_step: usize) -> Self {
+        Self::new(vec![
+            U64FibonacciObject {
+                x: 0,
+                y: 0,
+                y_next: 0,
+                carry: false,
+            };
+            num_iters_per_step
+        ])
+    }
+}
+
+pub fn u64_to_field<F: PrimeField>(value: u64) -> F {
+    F::from(value)
+}
+
+/// Inverse of `u64_to_field` for fields with a little-endian repr (the pasta fields).
+pub fn field_to_u64<F: PrimeField>(value: &F) -> u64 {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    debug_assert!(bytes[8..].iter().all(|b| *b == 0));
+
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+impl<F: PrimeField> StepCircuit<F> for U64FibonacciCircuit<F> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let two_pow_64 = F::from(u64::MAX) + F::ONE;
+
+        let mut x = z[0].clone();
+        let mut y = z[1].clone();
+
+        decompose_u64(cs.namespace(|| "range_x_0"), &x, self.values[0].x)?;
+        decompose_u64(cs.namespace(|| "range_y_0"), &y, self.values[0].y)?;
+
+        for (i, value) in self.values.iter().enumerate() {
+            let y_next = AllocatedNum::alloc(cs.namespace(|| format!("y_next_{i}")), || {
+                Ok(u64_to_field(value.y_next))
+            })?;
+            decompose_u64(
+                cs.namespace(|| format!("range_y_next_{i}")),
+                &y_next,
+                value.y_next,
+            )?;
+
+            let carry =
+                AllocatedBit::alloc(cs.namespace(|| format!("carry_{i}")), Some(value.carry))?;
+
+            cs.enforce(
+                || format!("(y_next_{i} + carry_{i} * 2^64) * 1 = x_{i} + y_{i}"),
+                |lc| lc + y_next.get_variable() + (two_pow_64, carry.get_variable()),
+                |lc| lc + CS::one(),
+                |lc| lc + x.get_variable() + y.get_variable(),
+            );
+
+            x = y;
+            y = y_next;
+        }
+
+        Ok(vec![x, y])
+    }
+
+    fn output(&self, z: &[F]) -> Vec<F> {
+        let (x, y) = (0..self.values.len())
+            .fold((field_to_u64(&z[0]), field_to_u64(&z[1])), |(x, y), _| {
+                (y, x.wrapping_add(y))
+            });
+
+        vec![u64_to_field(x), u64_to_field(y)]
+    }
+}
+
+#[test]
+fn test_u64_reference() {
+    let (_, values) = U64FibonacciObject::new(100, 0, 1);
+
+    let mut fibonacci = (0u128, 1u128);
+    for value in &values {
+        fibonacci = (fibonacci.1, fibonacci.0 + fibonacci.1);
+        assert_eq!(value.y_next as u128, fibonacci.1 % (1 << 64));
+    }
+
+    // F(93) is the largest Fibonacci number that fits in a u64, F(94) wraps.
+    assert_eq!(values[91].y_next, 12200160415121876738);
+    assert!(!values[91].carry);
+    assert!(values[92].carry);
+    assert_eq!(values[92].y_next, 1293530146158671551);
+    assert!(values[..92].iter().all(|value| !value.carry));
+}
+
+#[test]
+fn test_u64_field_roundtrip() {
+    type F = pasta_curves::pallas::Scalar;
+
+    for value in [0, 1, 12200160415121876738, u64::MAX] {
+        assert_eq!(field_to_u64(&u64_to_field::<F>(value)), value);
+    }
+}
+
+#[test]
+fn test_u64_before_overflow() {
+    // 90 iterations end at (F(90), F(91)), before the first wraparound.
+    prove_and_verify_u64(3, 30).unwrap();
+}
+
+#[test]
+fn test_u64_first_overflow() {
+    // 96 iterations cross F(94), the first index that wraps around 2^64.
+    prove_and_verify_u64(3, 32).unwrap();
+}
+
+#[test]
+fn test_u64_tampered_carry() {
+    use crate::{error::Error, prove_verify::prove_and_verify_u64_circuits};
+
+    let (z0, mut values) = U64FibonacciObject::new(96, 0, 1);
+    // Drop the carry of F(94) and pretend the sum did not wrap.
+    values[92].carry = false;
+
+    let circuits = values
+        .chunks(32)
+        .map(|values| U64FibonacciCircuit::new(values.to_vec()))
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        prove_and_verify_u64_circuits(&z0, circuits),
+        Err(Error::Nova(_))
+    ));
+}
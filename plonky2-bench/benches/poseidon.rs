@@ -1,3 +1,4 @@
+use bench_alloc::{CountingAllocator, Phase};
 use core::time::Duration;
 use criterion::*;
 use plonky2::field::goldilocks_field::GoldilocksField;
@@ -20,6 +21,9 @@ criterion_group! {
 
 criterion_main!(recursive_snark);
 
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
 fn bench_recursive_snark_proove(c: &mut Criterion) {
     let cases = vec![3, 10, 100];
 
@@ -29,10 +33,12 @@ fn bench_recursive_snark_proove(c: &mut Criterion) {
 
         let d = d - 1;
 
+        let phase = Phase::start(format!("Plonky2-Poseidon-num-steps-{}/Setup", d + 1));
         let (builder, common_data, condition, inner_cyclic_proof_with_pis, verifier_data_target) =
             init(d).unwrap();
 
         let cyclic_circuit_data = builder.build::<C>();
+        println!("{}", phase.finish());
 
         // initial witness
         let mut pw = PartialWitness::new();
@@ -52,6 +58,7 @@ fn bench_recursive_snark_proove(c: &mut Criterion) {
         let mut proof: ProofWithPublicInputs<GoldilocksField, PoseidonGoldilocksConfig, D> =
             cyclic_circuit_data.prove(pw.clone()).unwrap();
 
+        let phase = Phase::start(format!("Plonky2-Poseidon-num-steps-{}/Prove", d + 1));
         group.bench_function("Prove", |b| {
             b.iter(|| {
                 proof = cyclic_circuit_data.prove(pw.clone()).unwrap();
@@ -76,6 +83,7 @@ fn bench_recursive_snark_proove(c: &mut Criterion) {
                 .unwrap();
             })
         });
+        println!("{}", phase.finish());
 
         let num_constr: usize = common_data
             .gates
@@ -155,9 +163,11 @@ fn bench_recursive_snark_verify(c: &mut Criterion) {
 
         check_hash_value(&proof).unwrap();
 
+        let phase = Phase::start(format!("Plonky2-Poseidon-num-steps-{}/Verify", d + 1));
         group.bench_function("Verify", |b| {
             b.iter(|| cyclic_circuit_data.verify(proof.clone()))
         });
+        println!("{}", phase.finish());
 
         group.finish();
     }
@@ -1,3 +1,4 @@
+use bench_alloc::{measure, CountingAllocator, Phase};
 use criterion::{criterion_group, criterion_main, Criterion};
 use plonky2::field::types::Sample;
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
@@ -13,6 +14,9 @@ type C = PoseidonGoldilocksConfig;
 type F = <C as GenericConfig<D>>::F;
 type S = PoseidonStark<F, D>;
 
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator::new();
+
 fn bench_poseidon_starky(c: &mut Criterion) {
     let mut config = StarkConfig::standard_fast_config();
     config.fri_config.cap_height = 0;
@@ -28,10 +32,13 @@ fn bench_poseidon_starky(c: &mut Criterion) {
     }
 
     let stark = S::default();
-    let trace = generate_poseidon_trace(&step_rows);
-    let trace_poly_values = trace_to_poly_values(trace);
+    let (trace_poly_values, memory) = measure("poseidon_starky/Trace", || {
+        trace_to_poly_values(generate_poseidon_trace(&step_rows))
+    });
+    println!("{memory}");
 
     let mut timing = TimingTree::default();
+    let phase = Phase::start("poseidon_starky/Prove");
     c.bench_function("poseidon_starky", |b| {
         b.iter_batched(
             || trace_poly_values.clone(),
@@ -41,6 +48,7 @@ fn bench_poseidon_starky(c: &mut Criterion) {
             criterion::BatchSize::SmallInput,
         );
     });
+    println!("{}", phase.finish());
 }
 
 